@@ -23,6 +23,87 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// All the argument types must be (de)serializable for the `Call` to be. We give these bounds
+	// explicitly, since serde does not infer them for every argument type, like `Box<T::Call>`.
+	let serialize_bound = args_type
+		.iter()
+		.flatten()
+		.map(|type_| format!("{}: serde::Serialize", quote!(#type_)))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let deserialize_bound = args_type
+		.iter()
+		.flatten()
+		.map(|type_| format!("{}: serde::Deserialize<'de>", quote!(#type_)))
+		.collect::<Vec<_>>()
+		.join(", ");
+
+	// All the argument types, used to bound the `Hash` implementation of the `Call`. Unlike a
+	// derive, this does not require `T` itself to implement `Hash`.
+	let all_args_type = args_type.iter().flatten().collect::<Vec<_>>();
+
+	// This is a vector of the weight of each of the functions in `fn_name`. Functions without a
+	// `#[weight(..)]` attribute have a weight of 1.
+	let weight = methods
+		.iter()
+		.map(|method| match &method.weight {
+			Some(weight) => quote!(crate::support::Weight::new(#weight)),
+			None => quote!(crate::support::Weight::new(1)),
+		})
+		.collect::<Vec<_>>();
+
+	// This is a vector of the code which checks the origin of each of the functions in `fn_name`,
+	// and gives the `caller` to pass on to the function, if it is signed. Unsigned functions must
+	// be called with `Origin::None`, and all others with `Origin::Signed`.
+	let caller = methods
+		.iter()
+		.map(|method| match method.unsigned {
+			true => quote!(crate::support::Origin::ensure_none(&origin)?;),
+			false => quote!(let caller = crate::support::Origin::ensure_signed(origin)?;),
+		})
+		.collect::<Vec<_>>();
+	let caller_arg = methods
+		.iter()
+		.map(|method| match method.unsigned {
+			true => quote!(),
+			false => quote!(caller,),
+		})
+		.collect::<Vec<_>>();
+
+	// This is a vector of the code which checks whether each of the functions in `fn_name` would
+	// succeed. Functions without a `#[validate(..)]` attribute are always assumed to succeed, as
+	// long as they are called with the right origin.
+	let validate = methods
+		.iter()
+		.zip(&args_name)
+		.map(|(method, args_name)| {
+			let fn_name = &method.name;
+			match (&method.validate, method.unsigned) {
+				(Some(validate), true) => quote! {
+					Call::#fn_name { #( #args_name ),* } => {
+						origin.ensure_none()?;
+						self.#validate(#( #args_name ),*)
+					},
+				},
+				(Some(validate), false) => quote! {
+					Call::#fn_name { #( #args_name ),* } => {
+						let caller = origin.signer().ok_or(crate::support::DispatchError::BadOrigin)?;
+						self.#validate(caller, #( #args_name ),*)
+					},
+				},
+				(None, true) => quote! {
+					Call::#fn_name { .. } => origin.ensure_none(),
+				},
+				(None, false) => quote! {
+					Call::#fn_name { .. } => {
+						origin.signer().ok_or(crate::support::DispatchError::BadOrigin)?;
+						Ok(())
+					},
+				},
+			}
+		})
+		.collect::<Vec<_>>();
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
 	// and the `Dispatch` trait logic to route a `caller` to access those functions.
 	let dispatch_impl = quote! {
@@ -30,25 +111,71 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone)]
+		#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+		#[cfg_attr(
+			feature = "serde",
+			serde(bound(serialize = #serialize_bound, deserialize = #deserialize_bound))
+		)]
 		pub enum Call<T: Config> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
 		}
 
+		impl<T: Config> Call<T> {
+			// The weight of executing this call, which counts towards the weight limit of a block.
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						Call::#fn_name { .. } => #weight,
+					)*
+				}
+			}
+
+			// The name of the function this call is for.
+			pub fn name(&self) -> &'static str {
+				match self {
+					#(
+						Call::#fn_name { .. } => stringify!(#fn_name),
+					)*
+				}
+			}
+		}
+
+		// The hash of a call covers the function and all of its arguments, so two calls have the same
+		// hash when they would do the same thing.
+		impl<T: Config> core::hash::Hash for Call<T>
+		where
+			#( #all_args_type: core::hash::Hash, )*
+		{
+			fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+				core::mem::discriminant(self).hash(state);
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => {
+							#( #args_name.hash(state); )*
+						},
+					)*
+				}
+			}
+		}
+
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
-		// appropriate function call with all arguments, including the `caller`.
+		// appropriate function call with all arguments, including the `caller` of signed calls.
 		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
-			type Caller = T::AccountId;
+			type Caller = crate::support::Origin<T::AccountId>;
 			type Call = Call<T>;
 
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
+			fn dispatch(&mut self, origin: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
 				match call {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
+							#caller
 							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
+								// Note that we assume the first argument of every signed call is the
+								// `caller`.
+								#caller_arg
 								#( #args_name ),*
 							)?;
 						},
@@ -56,6 +183,18 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 				}
 				Ok(())
 			}
+
+			// Check whether the call would succeed, with the validation method of the function, if
+			// it has one.
+			fn can_dispatch(
+				&self,
+				origin: &Self::Caller,
+				call: &Self::Call,
+			) -> crate::support::DispatchResult {
+				match call {
+					#( #validate )*
+				}
+			}
 		}
 	};
 
@@ -25,6 +25,14 @@ pub struct CallVariantDef {
 	pub name: syn::Ident,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// The weight given with the `#[weight(..)]` attribute of the function, if any.
+	pub weight: Option<syn::Expr>,
+	/// The method given with the `#[validate(..)]` attribute of the function, if any, which checks
+	/// whether a call would succeed without dispatching it.
+	pub validate: Option<syn::Ident>,
+	/// Whether the function has the `#[unsigned]` attribute, in which case it can only be called
+	/// without a signer, and does not take a `caller` argument.
+	pub unsigned: bool,
 }
 
 impl CallDef {
@@ -59,23 +67,33 @@ impl CallDef {
 					},
 				}
 
-				// The second argument should be the `caller: T::AccountId` argument.
-				match method.sig.inputs.iter().skip(1).next() {
-					Some(syn::FnArg::Typed(arg)) => {
-						// Here we specifically check that this argument is as we expect for
-						// `caller: T::AccountId`.
-						check_caller_arg(arg)?;
-					},
-					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
-						return Err(syn::Error::new(method.sig.span(), msg))
-					},
+				// Unsigned functions have no `caller` argument.
+				let unsigned = method.attrs.iter().any(|attr| attr.path().is_ident(UNSIGNED_ATTR));
+
+				// Otherwise, the second argument should be the `caller: T::AccountId` argument.
+				if !unsigned {
+					match method.sig.inputs.iter().skip(1).next() {
+						Some(syn::FnArg::Typed(arg)) => {
+							// Here we specifically check that this argument is as we expect for
+							// `caller: T::AccountId`.
+							check_caller_arg(arg)?;
+						},
+						_ => {
+							let msg =
+								"Invalid call, second argument should be `caller: T::AccountId`";
+							return Err(syn::Error::new(method.sig.span(), msg))
+						},
+					}
 				}
 
 				let fn_name = method.sig.ident.clone();
+				let weight = parse_weight_attr(&method.attrs)?;
+				let validate = parse_validate_attr(&method.attrs)?;
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
-				for arg in method.sig.inputs.iter().skip(2) {
+				// Parsing the rest of the args. Skipping 2 for `self` and `caller`, or only 1 for
+				// `self` if the function is unsigned.
+				let skip = if unsigned { 1 } else { 2 };
+				for arg in method.sig.inputs.iter().skip(skip) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
 						arg
@@ -95,8 +113,9 @@ impl CallDef {
 					args.push((arg_ident, arg.ty.clone()));
 				}
 
-				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				// Store all the function name, the arg data, the weight, the validation method and
+				// the origin for the function.
+				methods.push(CallVariantDef { name: fn_name, args, weight, validate, unsigned });
 			}
 		}
 
@@ -105,6 +124,44 @@ impl CallDef {
 	}
 }
 
+/// Parse the optional `#[weight(..)]` attribute of a callable function, which contains an
+/// expression of the weight as a `u64`.
+pub fn parse_weight_attr(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Expr>> {
+	let mut weight = None;
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident(WEIGHT_ATTR)) {
+		if weight.is_some() {
+			let msg = "Invalid call, only one `#[weight(..)]` attribute is allowed";
+			return Err(syn::Error::new(attr.span(), msg))
+		}
+		weight = Some(attr.parse_args::<syn::Expr>()?);
+	}
+	Ok(weight)
+}
+
+/// The name of the attribute used to give the weight of a callable function.
+pub const WEIGHT_ATTR: &str = "weight";
+
+/// Parse the optional `#[validate(..)]` attribute of a callable function, which contains the name
+/// of a method of the pallet. The method takes `&self`, and a reference to the `caller` and to each
+/// of the arguments of the function, and returns a `DispatchResult`.
+pub fn parse_validate_attr(attrs: &[syn::Attribute]) -> syn::Result<Option<syn::Ident>> {
+	let mut validate = None;
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident(VALIDATE_ATTR)) {
+		if validate.is_some() {
+			let msg = "Invalid call, only one `#[validate(..)]` attribute is allowed";
+			return Err(syn::Error::new(attr.span(), msg))
+		}
+		validate = Some(attr.parse_args::<syn::Ident>()?);
+	}
+	Ok(validate)
+}
+
+/// The name of the attribute used to give the validation method of a callable function.
+pub const VALIDATE_ATTR: &str = "validate";
+
+/// The name of the attribute used to mark a callable function as unsigned.
+pub const UNSIGNED_ATTR: &str = "unsigned";
+
 /// Check caller arg is exactly: `caller: T::AccountId`.
 ///
 /// This is kept strict to keep the code simple.
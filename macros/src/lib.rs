@@ -1,6 +1,17 @@
 mod call;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// This generates an `enum Call` with a variant for each function in the `impl` block, and
+/// implements the trait `support::Dispatch` for the pallet. Each function can be given a weight
+/// with a `#[weight(..)]` attribute; functions without one have a weight of 1. Each function can
+/// also be given a method which checks whether a call would succeed, with a
+/// `#[validate(method)]` attribute, which `Dispatch::can_dispatch` uses; functions without one are
+/// assumed to succeed. Each call also has a `name`, the name of its function. Calls are dispatched
+/// with a `support::Origin`: functions must be called with `Origin::Signed(caller)`, and take that
+/// `caller` as their first argument, unless they have an `#[unsigned]` attribute, in which case
+/// they must be called with `Origin::None`, and take no `caller` argument.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -11,20 +22,71 @@ pub fn call(
 
 /// Expand the `Runtime` definition.
 ///
+/// A runtime can include any of the pallets, as long as `system` is its first field. The pallets
+/// which work together, as described below, are only connected when the runtime includes all of
+/// them, except for the treasury, the vesting and the voting, which require a currency. The runtime
+/// finds these pallets by the role their field is marked with, whatever the name of the field is:
+/// - `#[pallet(currency)]` - a `balances` pallet, which holds the funds of the accounts.
+/// - `#[pallet(treasury)]` - a `treasury` pallet.
+/// - `#[pallet(scheduler)]` - a `scheduler` pallet.
+/// - `#[pallet(multisig)]` - a `multisig` pallet.
+/// - `#[pallet(vesting)]` - a `vesting` pallet.
+/// - `#[pallet(voting)]` - a `voting` pallet.
+///
+/// The voting and the other pallets can be gated by `#[cfg(...)]` attributes on their fields, such
+/// as `#[cfg(feature = "nft")]`, so the runtime can be compiled with only some of them. The code
+/// generated for a pallet is gated by the same attributes. Since the index of each pallet is its
+/// position in the struct, leaving a pallet out does not change the indices of the others.
+///
 /// This generates function implementations on `Runtime`:
 /// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
 ///   included in the runtime.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
-///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   basic actions like incrementing the block number, checking the block to be executed has a
+///   valid block number (and has extrinsics, if the system pallet rejects empty blocks), and
+///   rejecting the extrinsics which exceed the weight limit of the block. If the runtime includes a
+///   currency, the caller of each extrinsic pays the fee of its call, as given by
+///   `balances::Config::weight_to_fee`, before it is dispatched. If the runtime also includes a
+///   treasury, the fee is collected into its pot. If the runtime includes a scheduler, the calls it
+///   has scheduled for the block are dispatched before the extrinsics, taking up weight in the
+///   block and paying their fees like extrinsics do. Every pallet (except system) must implement
+///   `support::Hooks`: `on_initialize` is called before the extrinsics of the block, and
+///   `on_finalize` after them. The events of every pallet are collected into the system pallet
+///   after each of these steps, along with the accounts the currency has created or reaped, if the
+///   runtime includes one. Every pallet must implement `support::OnKilledAccount`, which is called
+///   for each account which was reaped and has no providers left, and `support::OnNewAccount`,
+///   which is called for each account which came into existence in the system pallet.
+/// - `fn execute_block_collect_errors()` - which executes a block like `execute_block`, but returns
+///   the errors of the failed extrinsics instead of logging them.
+/// - `fn upgrade()` - which migrates the storage of each pallet, including system, up to the
+///   version of its current code. Every pallet must implement `support::Migrate`.
+/// - `fn apply_extrinsic()` - which applies a single extrinsic, like `execute_block` does for each
+///   of its extrinsics, in the order of the block, without changing the block number. Before its
+///   call is dispatched, the extrinsic is checked by a pipeline of `support::SignedExtension`s,
+///   which stops at the first check which fails: `support::CheckNonce`, then
+///   `support::CheckBalance` if the runtime includes a currency, then the extra extensions given to
+///   the macro as `#[runtime(extensions = (A, B))]`, which must implement `Default`.
+/// - `fn pallet_index()` - which gives the index of the pallet which exposes a call: its position
+///   in the `Runtime` struct, where `system` has the index 0.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
 /// - Note: For simplicity, we assume that the system pallet is not callable.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
 ///   all pallets. The system pallet is not included.
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
-///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+///   logic like incrementing the nonce of the user is included in the generated code. Extrinsics,
+///   scheduled calls and approved multisig calls are all dispatched as signed. The system pallet is
+///   not included. If the runtime includes a multisig, the call it has approved is dispatched on
+///   behalf of the multisig account right after the approval, and the approval is undone if that
+///   call fails. If the runtime includes a treasury, the spends it has approved are paid out by the
+///   currency right after the spend, and the spend is undone if its payout fails. If the runtime
+///   includes a vesting, the funds of the schedules it has added are withdrawn from the currency,
+///   with the checks and fee of a transfer to their beneficiary, and the funds it has released are
+///   paid into it, right after the call, which is undone if they cannot be. If the runtime includes
+///   a voting, the votes cast by a call are weighed by the balances of their voters in the currency
+///   right after it. A runtime which includes any of them must implement `Clone`. Calls which the
+///   system pallet has paused, by the names of their pallet and function, are rejected before they
+///   are routed.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
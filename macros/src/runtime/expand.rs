@@ -1,14 +1,106 @@
-use super::parse::RuntimeDef;
+use super::parse::{Roles, RuntimeDef};
 use quote::quote;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
-	let RuntimeDef { runtime_struct, pallets } = def;
+	let RuntimeDef { runtime_struct, pallets, extensions, roles, .. } = def;
+	let Roles { currency, treasury, scheduler, multisig, vesting, voting } = roles;
 
 	// This is a vector of all the pallet names, not including system.
-	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	let pallet_names = pallets.iter().map(|(name, ..)| name.clone()).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
-	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	let pallet_types = pallets.iter().map(|(_, type_, _)| type_.clone()).collect::<Vec<_>>();
+	// This is a vector of the `#[cfg]` attributes of each pallet, not including system, which are
+	// put on all the code generated for the pallet, so that code is left out along with its field.
+	let pallet_cfgs = pallets.iter().map(|(.., cfgs)| quote!(#(#cfgs)*)).collect::<Vec<_>>();
+	// This is a vector of the index of each pallet in the runtime, not including system, which
+	// always has the index 0.
+	let pallet_indices = (1..=pallets.len()).map(|index| index as u8).collect::<Vec<_>>();
+
+	// If the runtime includes a currency, the accounts it has created or reaped are passed on to
+	// the system pallet whenever the events are collected, so it can count the accounts which
+	// exist. A stored balance is a provider of its account. Every pallet is told about the
+	// accounts which were reaped once they have no providers left, so it can clean up after them.
+	let collect_account_changes = currency.as_ref().map(|currency| {
+		quote! {
+			for change in self.#currency.take_account_changes() {
+				match change {
+					crate::balances::AccountChange::Created(who) => self.system.inc_providers(&who),
+					crate::balances::AccountChange::Reaped(who) => if self.system.dec_providers(&who) {
+						#(
+							#pallet_cfgs
+							crate::support::OnKilledAccount::on_killed_account(
+								&mut self.#pallet_names,
+								&who,
+							);
+						)*
+					},
+				}
+			}
+		}
+	});
+
+	// If the runtime includes a scheduler, the calls it has scheduled for a block are dispatched at
+	// the start of that block, after the `on_initialize` hooks. Like the call of an extrinsic, each
+	// of them takes up its weight in the block and its fee is paid by the account which scheduled
+	// it. Calls which do not fit in the remaining weight of the block, or whose fee cannot be paid,
+	// are dropped with their error.
+	let dispatch_agenda = scheduler.as_ref().map(|scheduler| {
+		quote! {
+			let agenda = self.#scheduler.take_agenda(&block.header.block_number);
+			for (index, (caller, call)) in agenda.into_iter().enumerate() {
+				let result = self.charge_call(&caller, &call).and_then(|()| {
+					let origin = crate::support::Origin::Signed(caller);
+					crate::support::Dispatch::dispatch(self, origin, call)
+				});
+				self.#scheduler.note_dispatched(block.header.block_number, index as u32, result);
+				self.collect_events();
+			}
+		}
+	});
+
+	// If the runtime includes a currency, the caller of each extrinsic pays the fee of its call
+	// before it is dispatched. If it also includes a treasury, the fee is collected into its pot.
+	let deposit_fee = treasury.as_ref().map(|treasury| quote!(self.#treasury.deposit(fee)?;));
+	let charge_fee = currency.as_ref().map(|currency| {
+		quote! {
+			let fee = call.fee();
+			self.#currency.deduct_fee(caller, fee)?;
+			#deposit_fee
+		}
+	});
+	// The signed extensions which check each extrinsic before its call is dispatched: the nonce,
+	// then the fee if the runtime includes a currency, then the extra extensions given to the
+	// macro, which are created with `Default`.
+	let check_balance = currency.as_ref().map(|_| quote!(crate::support::CheckBalance,));
+	let check_balance_impl = currency.as_ref().map(|currency| {
+		quote! {
+			impl crate::support::SignedExtension<
+				#runtime_struct,
+				types::AccountId,
+				RuntimeCall,
+			> for crate::support::CheckBalance {
+				fn validate(
+					&self,
+					runtime: &#runtime_struct,
+					caller: &types::AccountId,
+					call: &RuntimeCall,
+				) -> crate::support::DispatchResult {
+					runtime.#currency.can_deduct_fee(caller, &call.fee())
+				}
+			}
+		}
+	});
+	let fee_impl = currency.as_ref().map(|_| {
+		quote! {
+			impl RuntimeCall {
+				// The fee paid by the caller of this call, which is derived from its weight.
+				pub fn fee(&self) -> <#runtime_struct as crate::balances::Config>::Balance {
+					<#runtime_struct as crate::balances::Config>::weight_to_fee(self.weight())
+				}
+			}
+		}
+	});
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
@@ -19,31 +111,250 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 					// Since system is not included in the list of pallets, we manually add it here.
 					system: <system::Pallet::<Self>>::new(),
 					#(
+						#pallet_cfgs
 						#pallet_names: <#pallet_types>::new()
 					),*
 				}
 			}
 
+			// Upgrade the runtime, by migrating the storage of each pallet (including system) from the
+			// version of its state to the version of its current code, if the state is older.
+			pub fn upgrade(&mut self) {
+				let from = crate::support::Migrate::storage_version(&self.system);
+				let to = <system::Pallet::<Self> as crate::support::Migrate>::STORAGE_VERSION;
+				if from < to {
+					crate::support::Migrate::migrate(&mut self.system, from, to);
+				}
+				#(
+					#pallet_cfgs
+					{
+						let from = crate::support::Migrate::storage_version(&self.#pallet_names);
+						let to = <#pallet_types as crate::support::Migrate>::STORAGE_VERSION;
+						if from < to {
+							crate::support::Migrate::migrate(&mut self.#pallet_names, from, to);
+						}
+					}
+				)*
+			}
+
+			// The index of the pallet which exposes the `call`, which is the position of the pallet
+			// in the runtime struct. The system pallet comes first, with the index 0.
+			pub fn pallet_index(call: &RuntimeCall) -> u8 {
+				match call {
+					#(
+						#pallet_cfgs
+						RuntimeCall::#pallet_names(_) => #pallet_indices,
+					)*
+				}
+			}
+
 			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
-				self.system.inc_block_number();
-				if block.header.block_number != self.system.block_number() {
-					return Err(&"block number does not match what is expected")
-				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
-					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
-					});
+			//
+			// The block must be the next block of the chain, otherwise it is rejected before any
+			// state is changed. Errors from individual extrinsics are logged, but do not abort the
+			// execution of the block.
+			//
+			// The extrinsics are applied in the order they appear in the block, whichever pallets
+			// their calls are for, so executing the same block on the same state always leads to
+			// the same state. They are not reordered by pallet, since the nonces of the extrinsics
+			// of each caller must follow their order in the block.
+			pub fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
+				let block_number = block.header.block_number;
+				for (i, e) in self.execute_block_collect_errors(block)? {
+					eprintln!(
+						"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
+						block_number, i, e
+					)
 				}
 				Ok(())
 			}
+
+			// Execute a block of extrinsics, like `execute_block`, but instead of logging the errors
+			// of failed extrinsics, return the index and error of each of them.
+			pub fn execute_block_collect_errors(
+				&mut self,
+				block: types::Block,
+			) -> Result<Vec<(usize, crate::support::DispatchError)>, crate::support::DispatchError> {
+				let expected_block_number = num::CheckedAdd::checked_add(
+					&self.system.block_number(),
+					&<types::BlockNumber as num::One>::one(),
+				)
+				.ok_or(crate::support::DispatchError::BlockNumberOverflow)?;
+				if block.header.block_number != expected_block_number {
+					return Err(crate::support::DispatchError::InvalidBlockNumber)
+				}
+				if block.extrinsics.is_empty() && self.system.reject_empty_blocks() {
+					return Err(crate::support::DispatchError::EmptyBlock)
+				}
+				self.system.inc_block_number()?;
+				#(
+					#pallet_cfgs
+					crate::support::Hooks::on_initialize(
+						&mut self.#pallet_names,
+						block.header.block_number,
+					);
+				)*
+				self.collect_events();
+				#dispatch_agenda
+				let mut errors = Vec::new();
+				for (i, extrinsic) in block.extrinsics.into_iter().enumerate() {
+					self.system.set_phase(system::Phase::ApplyExtrinsic(i as u32));
+					if let Err(e) = self.apply_extrinsic(extrinsic) {
+						errors.push((i, e));
+					}
+				}
+				self.system.set_phase(system::Phase::Finalization);
+				#(
+					#pallet_cfgs
+					crate::support::Hooks::on_finalize(
+						&mut self.#pallet_names,
+						block.header.block_number,
+					);
+				)*
+				self.collect_events();
+				Ok(errors)
+			}
+
+			// Apply a single extrinsic on top of the current block, without touching the block
+			// number: increment the nonce of its caller and dispatch its call.
+			//
+			// Extrinsics which do not carry the expected nonce, which do not fit in the remaining
+			// weight of the block, or whose caller cannot pay the fee, are skipped, without
+			// incrementing the nonce of the caller or the extrinsic count of the block.
+			pub fn apply_extrinsic(
+				&mut self,
+				extrinsic: types::Extrinsic,
+			) -> crate::support::DispatchResult {
+				let support::Extrinsic { caller, nonce, call } = extrinsic;
+				let result = if let Err(e) = crate::support::SignedExtension::validate(
+					&Self::signed_extensions(nonce),
+					self,
+					&caller,
+					&call,
+				) {
+					Err(e)
+				} else if let Err(e) = self.charge_call(&caller, &call) {
+					Err(e)
+				} else {
+					self.system.inc_nonce(&caller).and_then(|()| {
+						self.system.note_extrinsic();
+						let origin = crate::support::Origin::Signed(caller);
+						crate::support::Dispatch::dispatch(self, origin, call)
+					})
+				};
+				self.collect_events();
+				result
+			}
+
+			// Take up the weight of the `call` in the current block, and charge its fee to the
+			// `caller`.
+			fn charge_call(
+				&mut self,
+				caller: &types::AccountId,
+				call: &RuntimeCall,
+			) -> crate::support::DispatchResult {
+				self.system.register_weight(call.weight())?;
+				#charge_fee
+				Ok(())
+			}
+
+			// The pipeline of signed extensions which checks an extrinsic carrying `nonce`, before
+			// its call is dispatched.
+			fn signed_extensions(nonce: types::Nonce) -> impl crate::support::SignedExtension<
+				Self,
+				types::AccountId,
+				RuntimeCall,
+			> {
+				(
+					crate::support::CheckNonce(nonce),
+					#check_balance
+					#( <#extensions as Default>::default(), )*
+				)
+			}
+
+			// Move the events buffered by each pallet into the event buffer of the system pallet.
+			fn collect_events(&mut self) {
+				#(
+					#pallet_cfgs
+					{
+						let events = crate::support::TakeEvents::<
+							<#runtime_struct as system::Config>::RuntimeEvent
+						>::take_events(&mut self.#pallet_names);
+						for event in events {
+							self.system.deposit_event(event);
+						}
+					}
+				)*
+				#collect_account_changes
+				// The accounts which came into existence are passed on to every pallet.
+				for who in self.system.take_new_accounts() {
+					#(
+						#pallet_cfgs
+						crate::support::OnNewAccount::on_new_account(&mut self.#pallet_names, &who);
+					)*
+				}
+			}
 		}
 	};
 
+	// This is a vector of the code which dispatches a call to each pallet. If the runtime includes
+	// a multisig, the call it has approved is then dispatched on behalf of the multisig account, as
+	// part of the same extrinsic, and a failure of that call undoes the approval too. If it
+	// includes a treasury, the spends it has approved are paid into the balances of their
+	// beneficiaries, and a failed payout undoes the spend. If it includes a vesting, the funds of
+	// the schedules it has added are withdrawn from the balances of the accounts which added them,
+	// like transfers to their beneficiaries, and the funds it has released are paid into the
+	// balances of their beneficiaries. If it includes a voting, the votes which have just been cast
+	// are weighed by the balances of their voters.
+	let dispatch_calls = pallet_names
+		.iter()
+		.map(|name| {
+			if multisig.as_ref() == Some(name) {
+				quote! {
+					crate::support::with_transaction(self, |runtime| {
+						runtime.#name.dispatch(origin, call)?;
+						if let Some((multisig, call)) = runtime.#name.take_ready_call() {
+							let origin = crate::support::Origin::Signed(multisig);
+							crate::support::Dispatch::dispatch(runtime, origin, call)?;
+						}
+						Ok(())
+					})?;
+				}
+			} else if treasury.as_ref() == Some(name) {
+				quote! {
+					crate::support::with_transaction(self, |runtime| {
+						runtime.#name.dispatch(origin, call)?;
+						for (to, amount) in runtime.#name.take_payouts() {
+							runtime.#currency.mint(&to, amount)?;
+						}
+						Ok(())
+					})?;
+				}
+			} else if vesting.as_ref() == Some(name) {
+				quote! {
+					crate::support::with_transaction(self, |runtime| {
+						runtime.#name.dispatch(origin, call)?;
+						for (from, to, amount) in runtime.#name.take_deposits() {
+							runtime.#currency.withdraw_transfer(&from, &to, amount)?;
+						}
+						for (to, amount) in runtime.#name.take_payouts() {
+							runtime.#currency.mint(&to, amount)?;
+						}
+						Ok(())
+					})?;
+				}
+			} else if voting.as_ref() == Some(name) {
+				quote! {
+					self.#name.dispatch(origin, call)?;
+					let currency = &self.#currency;
+					self.#name.weigh_votes(|who| currency.balance(who));
+				}
+			} else {
+				quote!(self.#name.dispatch(origin, call)?;)
+			}
+		})
+		.collect::<Vec<_>>();
+
 	// This quote block implements the `RuntimeCall` enum and implements the `Dispatch` trait.
 	let dispatch_impl = quote! {
 		// These are all the calls which are exposed to the world.
@@ -51,34 +362,110 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Debug, Clone, Hash)]
+		#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 		pub enum RuntimeCall {
-			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
+			#( #pallet_cfgs #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
+		}
+
+		impl RuntimeCall {
+			// The weight of executing this call, as given by the pallet which exposes it.
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						#pallet_cfgs
+						RuntimeCall::#pallet_names(call) => call.weight(),
+					)*
+				}
+			}
+
+			// The names of the pallet which exposes this call, and of the function it is for.
+			pub fn name(&self) -> (&'static str, &'static str) {
+				match self {
+					#(
+						#pallet_cfgs
+						RuntimeCall::#pallet_names(call) => (stringify!(#pallet_names), call.name()),
+					)*
+				}
+			}
 		}
 
+		#fee_impl
+
+		// An extrinsic must carry the next nonce of its caller, which must not overflow.
+		impl crate::support::SignedExtension<#runtime_struct, types::AccountId, RuntimeCall>
+			for crate::support::CheckNonce<types::Nonce>
+		{
+			fn validate(
+				&self,
+				runtime: &#runtime_struct,
+				caller: &types::AccountId,
+				_call: &RuntimeCall,
+			) -> crate::support::DispatchResult {
+				let expected_nonce = runtime.system.next_nonce(caller);
+				// The largest nonce has no next one, so the caller cannot make any more
+				// transactions.
+				if expected_nonce == runtime.system.nonce(caller) {
+					return Err(crate::support::DispatchError::NonceOverflow)
+				}
+				if self.0 != expected_nonce {
+					return Err(crate::support::DispatchError::InvalidNonce)
+				}
+				Ok(())
+			}
+		}
+
+		#check_balance_impl
+
 		impl crate::support::Dispatch for #runtime_struct {
-			type Caller = <Runtime as system::Config>::AccountId;
+			type Caller = crate::support::Origin<<#runtime_struct as system::Config>::AccountId>;
 			type Call = RuntimeCall;
 			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
 			//
 			// Dispatch allows us to identify which underlying pallet call we want to execute.
 			// Note that we extract the `caller` from the extrinsic, and use that information
-			// to determine who we are executing the call on behalf of.
+			// to determine who we are executing the call on behalf of. Each pallet checks that
+			// the call has the origin it expects.
 			fn dispatch(
 				&mut self,
-				caller: Self::Caller,
+				origin: Self::Caller,
 				runtime_call: Self::Call,
 			) -> crate::support::DispatchResult {
+				// Calls which the system pallet has paused are not routed at all.
+				let (pallet, call) = runtime_call.name();
+				if self.system.is_call_paused(pallet, call) {
+					return Err(crate::support::DispatchError::CallFiltered)
+				}
 				// This match statement will allow us to correctly route `RuntimeCall`s
 				// to the appropriate pallet level call.
 				match runtime_call {
 					#(
+						#pallet_cfgs
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							#dispatch_calls
 						}
 					),*
 				}
 				Ok(())
 			}
+
+			// Check whether dispatching a call would succeed, by asking the pallet which exposes it.
+			fn can_dispatch(
+				&self,
+				origin: &Self::Caller,
+				runtime_call: &Self::Call,
+			) -> crate::support::DispatchResult {
+				let (pallet, call) = runtime_call.name();
+				if self.system.is_call_paused(pallet, call) {
+					return Err(crate::support::DispatchError::CallFiltered)
+				}
+				match runtime_call {
+					#(
+						#pallet_cfgs
+						RuntimeCall::#pallet_names(call) => self.#pallet_names.can_dispatch(origin, call),
+					)*
+				}
+			}
 		}
 	};
 
@@ -6,12 +6,49 @@ pub struct RuntimeDef {
 	/// This is the name of the struct used by the user. We mostly assume it is `Runtime`.
 	pub runtime_struct: syn::Ident,
 	/// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
-	/// list, but during parsing we check that system exists.
-	pub pallets: Vec<(syn::Ident, syn::Type)>,
+	/// list, but during parsing we check that system exists. Each pallet comes with the `#[cfg]`
+	/// attributes of its field, so the code generated for it is only compiled along with it.
+	pub pallets: Vec<(syn::Ident, syn::Type, Vec<syn::Attribute>)>,
+	/// This is the list of the signed extensions given in the attribute of the macro, like
+	/// `#[runtime(extensions = (NoTransfers))]`, which run after the built-in checks of each
+	/// extrinsic.
+	pub extensions: Vec<syn::Type>,
+	/// These are the pallets which play a part in the logic of the runtime itself, as marked with
+	/// `#[pallet(...)]` on their fields.
+	pub roles: Roles,
+	/// This is the `Runtime` struct, without the `#[pallet(...)]` attributes of its fields, which
+	/// are only meant for this macro.
+	pub item_struct: syn::ItemStruct,
+}
+
+/// The pallets which the runtime itself works with, by the name of their field. A pallet is given
+/// a role by marking its field with `#[pallet(<role>)]`, whatever the name of the field is.
+#[derive(Debug, Default)]
+pub struct Roles {
+	/// The `balances` pallet, marked with `#[pallet(currency)]`, which holds the funds the fees
+	/// are paid from, and the accounts which keep the system pallet up to date.
+	pub currency: Option<syn::Ident>,
+	/// The `treasury` pallet, marked with `#[pallet(treasury)]`, which collects the fees, and
+	/// whose spends are paid out from the currency.
+	pub treasury: Option<syn::Ident>,
+	/// The `scheduler` pallet, marked with `#[pallet(scheduler)]`, whose calls are dispatched at
+	/// the start of the block they are scheduled for.
+	pub scheduler: Option<syn::Ident>,
+	/// The `multisig` pallet, marked with `#[pallet(multisig)]`, whose approved calls are
+	/// dispatched on behalf of the multisig account.
+	pub multisig: Option<syn::Ident>,
+	/// The `vesting` pallet, marked with `#[pallet(vesting)]`, whose schedules are funded from
+	/// the currency, and whose claims are paid out into it.
+	pub vesting: Option<syn::Ident>,
+	/// The `voting` pallet, marked with `#[pallet(voting)]`, whose votes are weighed by the
+	/// balances of the voters in the currency.
+	pub voting: Option<syn::Ident>,
 }
 
 impl RuntimeDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	pub fn try_from(attr: proc_macro2::TokenStream, item: syn::Item) -> syn::Result<Self> {
+		let extensions = parse_extensions(attr)?;
+
 		// First we check that we are parsing a `struct`.
 		let item_struct = if let syn::Item::Struct(item) = item {
 			item
@@ -22,19 +59,98 @@ impl RuntimeDef {
 		// We check that the `Runtime` includes the `system` pallet as the first item.
 		check_system(&item_struct)?;
 
-		let runtime_struct = item_struct.ident;
+		let mut item_struct = item_struct;
+		let runtime_struct = item_struct.ident.clone();
 
-		// Here is where we will store a list of all the pallets.
+		// Here is where we will store a list of all the pallets, and the roles some of them play.
 		let mut pallets = vec![];
+		let mut roles = Roles::default();
 		// We skip `system`, which we ensure is the first field in `check_system`.
-		for field in item_struct.fields.into_iter().skip(1) {
-			if let Some(ident) = field.ident {
-				pallets.push((ident, field.ty))
+		for field in item_struct.fields.iter_mut().skip(1) {
+			let Some(ident) = field.ident.clone() else { continue };
+			// Whether the pallet plays a role which the runtime relies on outside of its own calls.
+			let mut has_role = false;
+			for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("pallet")) {
+				let role: syn::Ident = attr.parse_args()?;
+				let slot = match role.to_string().as_str() {
+					"currency" => &mut roles.currency,
+					"treasury" => &mut roles.treasury,
+					"scheduler" => &mut roles.scheduler,
+					"multisig" => &mut roles.multisig,
+					"vesting" => &mut roles.vesting,
+					"voting" => &mut roles.voting,
+					_ => {
+						let msg =
+							"expected one of `currency`, `treasury`, `scheduler`, `multisig`, \
+							`vesting` or `voting`";
+						return Err(syn::Error::new(role.span(), msg))
+					},
+				};
+				if slot.is_some() {
+					let msg = format!("only one pallet can be the {}", role);
+					return Err(syn::Error::new(role.span(), msg))
+				}
+				*slot = Some(ident.clone());
+				// The votes are only weighed when a call of the voting is dispatched, so it can be
+				// left out like the pallets without a role.
+				has_role |= role != "voting";
+			}
+			field.attrs.retain(|attr| !attr.path().is_ident("pallet"));
+			let cfgs = field
+				.attrs
+				.iter()
+				.filter(|attr| attr.path().is_ident("cfg"))
+				.cloned()
+				.collect::<Vec<_>>();
+			// The runtime works with the pallets which play a role whenever it is compiled, so they
+			// cannot be left out of it.
+			if let (Some(cfg), true) = (cfgs.first(), has_role) {
+				let msg = "a pallet marked with `#[pallet(...)]` cannot be gated by `#[cfg]`";
+				return Err(syn::Error::new(cfg.span(), msg))
 			}
+			pallets.push((ident, field.ty.clone(), cfgs))
 		}
 
-		Ok(Self { runtime_struct, pallets })
+		// The spends of the treasury are paid out by the currency, so it cannot be included on its
+		// own.
+		if let (Some(treasury), None) = (&roles.treasury, &roles.currency) {
+			let msg = "the treasury requires a pallet marked with `#[pallet(currency)]`";
+			return Err(syn::Error::new(treasury.span(), msg))
+		}
+		// Likewise for the funds of the vesting schedules.
+		if let (Some(vesting), None) = (&roles.vesting, &roles.currency) {
+			let msg = "the vesting requires a pallet marked with `#[pallet(currency)]`";
+			return Err(syn::Error::new(vesting.span(), msg))
+		}
+		// And for the weights of the votes.
+		if let (Some(voting), None) = (&roles.voting, &roles.currency) {
+			let msg = "the voting requires a pallet marked with `#[pallet(currency)]`";
+			return Err(syn::Error::new(voting.span(), msg))
+		}
+
+		Ok(Self { runtime_struct, pallets, extensions, roles, item_struct })
+	}
+}
+
+/// This function parses the attribute of the macro, which is either empty, or gives the list of the
+/// extra signed extensions of the runtime as `extensions = (A, B, ...)`.
+fn parse_extensions(attr: proc_macro2::TokenStream) -> syn::Result<Vec<syn::Type>> {
+	if attr.is_empty() {
+		return Ok(vec![])
 	}
+	let parser = |input: syn::parse::ParseStream| {
+		let key: syn::Ident = input.parse()?;
+		if key != "extensions" {
+			return Err(syn::Error::new(key.span(), "expected `extensions = (...)`"))
+		}
+		input.parse::<syn::Token![=]>()?;
+		let content;
+		syn::parenthesized!(content in input);
+		let extensions =
+			syn::punctuated::Punctuated::<syn::Type, syn::Token![,]>::parse_terminated(&content)?;
+		Ok(extensions.into_iter().collect())
+	};
+	syn::parse::Parser::parse2(parser, attr)
 }
 
 /// This function checks that the `system` pallet is the first pallet included in the `Runtime`
@@ -0,0 +1,204 @@
+use num::{CheckedAdd, CheckedSub, Zero};
+
+use crate::support::{DispatchError, DispatchResult, StorageMap};
+
+pub trait Config: crate::system::Config {
+	/// The type used to identify each asset.
+	type AssetId: Ord + Copy;
+	/// The type used to represent the balance of an asset.
+	type Balance: Zero + CheckedSub + CheckedAdd + Copy + Default;
+}
+
+/// This is the Assets Module.
+/// It keeps track of how much of each fungible asset each account has, alongside the native
+/// balances of the balances module.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::AssetId: serde::Serialize, \
+			T::Balance: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, \
+			T::AssetId: serde::Deserialize<'de>, T::Balance: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A storage map from each asset to the account which created it.
+	owners: StorageMap<T::AssetId, T::AccountId>,
+	/// A storage map from an asset and an account to the balance of that asset the account has.
+	balances: StorageMap<(T::AssetId, T::AccountId), T::Balance>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Assets Module.
+	pub fn new() -> Self {
+		Self {
+			owners: StorageMap::new(),
+			balances: StorageMap::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the owner (if any) of an asset.
+	pub fn owner(&self, asset: &T::AssetId) -> Option<&T::AccountId> {
+		self.owners.get(asset)
+	}
+
+	/// Get the balance of `asset` held by the account `who`.
+	/// If the account has no stored balance, we return zero.
+	pub fn balance(&self, asset: T::AssetId, who: &T::AccountId) -> T::Balance {
+		self.balances.get_or_default(&(asset, who.clone()))
+	}
+
+	// Set the balance of `asset` held by the account `who` to some `amount`.
+	fn set_balance(&mut self, asset: T::AssetId, who: &T::AccountId, amount: T::Balance) {
+		self.balances.insert((asset, who.clone()), amount);
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create a new `asset`, owned by the `caller`.
+	/// This function will return an error if the asset already exists.
+	pub fn create_asset(&mut self, caller: T::AccountId, asset: T::AssetId) -> DispatchResult {
+		if self.owners.contains_key(&asset) {
+			return Err(DispatchError::AssetAlreadyExists);
+		}
+		self.owners.insert(asset, caller);
+		Ok(())
+	}
+
+	/// Mint `amount` of `asset` into the account `to`.
+	/// This function should only succeed if the caller is the owner of an existing asset, and no
+	/// mathematical overflows occur.
+	pub fn mint(
+		&mut self,
+		caller: T::AccountId,
+		asset: T::AssetId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let owner = self.owner(&asset).ok_or(DispatchError::AssetNotFound)?;
+		if *owner != caller {
+			return Err(DispatchError::NotOwner);
+		}
+
+		let new_to_balance =
+			self.balance(asset, &to).checked_add(&amount).ok_or(DispatchError::Overflow)?;
+		self.set_balance(asset, &to, new_to_balance);
+
+		Ok(())
+	}
+
+	/// Transfer `amount` of `asset` from one account to another.
+	/// This function verifies that the asset exists, that `from` has at least `amount` of it to
+	/// transfer, and that no mathematical overflows occur.
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		asset: T::AssetId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if !self.owners.contains_key(&asset) {
+			return Err(DispatchError::AssetNotFound);
+		}
+
+		let caller_balance = self.balance(asset, &caller);
+		let to_balance = self.balance(asset, &to);
+
+		let new_caller_balance =
+			caller_balance.checked_sub(&amount).ok_or(DispatchError::InsufficientBalance)?;
+		let new_to_balance = to_balance.checked_add(&amount).ok_or(DispatchError::Overflow)?;
+
+		self.set_balance(asset, &caller, new_caller_balance);
+		self.set_balance(asset, &to, new_to_balance);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::support::DispatchError;
+
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	impl super::Config for TestConfig {
+		type AssetId = u32;
+		type Balance = u128;
+	}
+
+	#[test]
+	fn create_and_mint_asset() {
+		let mut assets = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(assets.mint("alice", 1, "alice", 100), Err(DispatchError::AssetNotFound));
+
+		assert!(assets.create_asset("alice", 1).is_ok());
+		assert_eq!(assets.create_asset("bob", 1), Err(DispatchError::AssetAlreadyExists));
+		assert_eq!(assets.owner(&1), Some(&"alice"));
+
+		// Only the owner of the asset can mint it.
+		assert_eq!(assets.mint("bob", 1, "bob", 100), Err(DispatchError::NotOwner));
+		assert!(assets.mint("alice", 1, "bob", 100).is_ok());
+		assert_eq!(assets.balance(1, &"bob"), 100);
+
+		assert_eq!(assets.mint("alice", 1, "bob", u128::MAX), Err(DispatchError::Overflow));
+		assert_eq!(assets.balance(1, &"bob"), 100);
+	}
+
+	#[test]
+	fn transfer_asset() {
+		let mut assets = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		assert!(assets.create_asset(alice, 1).is_ok());
+		assert!(assets.create_asset(alice, 2).is_ok());
+		assert!(assets.mint(alice, 1, alice, 100).is_ok());
+		assert!(assets.mint(alice, 2, alice, 50).is_ok());
+
+		assert!(assets.transfer(alice, 1, bob, 30).is_ok());
+		assert_eq!(assets.balance(1, &alice), 70);
+		assert_eq!(assets.balance(1, &bob), 30);
+
+		// The balances of the other asset are not affected.
+		assert_eq!(assets.balance(2, &alice), 50);
+		assert_eq!(assets.balance(2, &bob), 0);
+
+		assert_eq!(assets.transfer(bob, 2, alice, 1), Err(DispatchError::InsufficientBalance));
+		assert_eq!(assets.transfer(alice, 3, bob, 1), Err(DispatchError::AssetNotFound));
+	}
+}
@@ -1,100 +1,2389 @@
-use num::{CheckedAdd, CheckedSub, Zero};
-use std::collections::BTreeMap;
+use core::fmt::Display;
+use num::{CheckedAdd, CheckedSub, Saturating, Zero};
+use std::collections::BTreeSet;
+
+use crate::support::{
+	DispatchError, DispatchResult, Hooks, Perbill, StorageMap, TakeEvents, Weight,
+};
 
 pub trait Config: crate::system::Config {
-	type Balance: Zero + CheckedSub + CheckedAdd + Copy;
+	type Balance: Zero
+		+ CheckedSub
+		+ CheckedAdd
+		+ Saturating
+		+ Copy
+		+ PartialOrd
+		+ Default
+		+ Display
+		+ Into<u128>
+		+ TryFrom<u128>;
+
+	/// The number of decimals of the token, which balances are displayed with. A balance of 1 is
+	/// the smallest fraction of a token.
+	const DECIMALS: u8 = 0;
+
+	/// The symbol of the token, which balances are displayed with.
+	fn symbol() -> &'static str {
+		"UNIT"
+	}
+
+	/// The minimum balance an account must keep to stay in storage.
+	/// Accounts which are left with less than this after sending funds are removed entirely.
+	fn minimum_balance() -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// Convert the weight of a call into the fee its caller pays for it.
+	/// By default, calls are free.
+	fn weight_to_fee(_weight: Weight) -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// The account which receives the fees paid for calls. If there is none, fees are burned.
+	fn fee_destination() -> Option<Self::AccountId> {
+		None
+	}
+
+	/// The balance of the accounts which have no stored balance yet, for example a faucet
+	/// balance which every new account starts with. It is not part of the total issuance.
+	fn initial_balance() -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// The amount of new tokens minted to the block author at the start of each block.
+	fn block_reward() -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// The author of the blocks, who receives the block reward. If there is none, no reward is
+	/// minted.
+	fn author() -> Option<Self::AccountId> {
+		None
+	}
+
+	/// The largest amount which can be moved in a single transfer. If there is none, transfers
+	/// are unlimited.
+	fn max_transfer() -> Option<Self::Balance> {
+		None
+	}
+
+	/// The proportion of the amount of a transfer which its sender pays as a fee, on top of the
+	/// amount, rounded down. The fee goes to the fee destination, like the fees of calls.
+	/// By default, transfers are free.
+	fn transfer_fee() -> Perbill {
+		Perbill::ZERO
+	}
+
+	/// The maximum length, in bytes, of the memo which can be attached to a transfer.
+	fn max_memo_len() -> usize {
+		32
+	}
+
+	/// Whether transfers can only be made into accounts which exist, because they already have a
+	/// stored balance, or were created explicitly. By default, transfers create new accounts.
+	fn require_account_creation() -> bool {
+		false
+	}
+
+	/// The amount the faucet mints to an account on each drip.
+	fn drip_amount() -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// The number of blocks an account has to wait after a drip from the faucet before it can get
+	/// another one.
+	fn cooldown_blocks() -> Self::BlockNumber {
+		Self::BlockNumber::zero()
+	}
+
+	/// The balance below which an account cannot burn its funds, so a protocol reserve is kept.
+	/// By default, there is no floor.
+	fn reserve_floor() -> Self::Balance {
+		Self::Balance::zero()
+	}
+
+	/// Whether an account can burn all of its funds, down to zero, even though zero is below the
+	/// reserve floor.
+	fn allow_burn_to_zero() -> bool {
+		true
+	}
+
+	/// The number of blocks the funds requested to be unlocked from a lock stay locked, before
+	/// they can be withdrawn.
+	fn bonding_period() -> Self::BlockNumber {
+		Self::BlockNumber::zero()
+	}
+}
+
+/// A change to the lifecycle of an account, which the runtime passes on to the system pallet, so
+/// it can keep track of the accounts which exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountChange<AccountId> {
+	/// The account got a stored balance.
+	Created(AccountId),
+	/// The stored balance of the account was removed.
+	Reaped(AccountId),
+}
+
+/// The composition of the balance of an account, for example to display it in a wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountData<Balance> {
+	/// The balance which the account can use, including the part of it which is locked.
+	pub free: Balance,
+	/// The balance which has been reserved, and is not part of the free balance.
+	pub reserved: Balance,
+	/// The part of the free balance which is currently locked, so it cannot be transferred.
+	pub frozen: Balance,
+}
+
+/// The events emitted by the balances module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<T: Config> {
+	/// `amount` was transferred from one account to another, leaving them with the balances
+	/// `from_balance_after` and `to_balance_after`.
+	Transferred {
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+		from_balance_after: T::Balance,
+		to_balance_after: T::Balance,
+	},
+	/// The balance of `who` was set directly to `free`.
+	BalanceSet { who: T::AccountId, free: T::Balance },
+	/// `amount` was transferred from one account to another, with a `memo` attached, for example
+	/// to identify the payment.
+	TransferredWithMemo { from: T::AccountId, to: T::AccountId, amount: T::Balance, memo: Vec<u8> },
 }
 
 /// Balances module
 /// Keeps track of how much balance each account has in this state machine
 /// NOT how pallet storage works in Polkadot SDK just a simple emulation of the behaviours
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::Balance: serde::Serialize, \
+			T::BlockNumber: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, T::Balance: serde::Deserialize<'de>, \
+			T::BlockNumber: serde::Deserialize<'de>",
+	))
+)]
 pub struct Pallet<T: Config> {
 	// A simple storage mapping from accounts (`String`) to their balances (`u128`).
-	balances: BTreeMap<T::AccountId, T::Balance>,
+	balances: StorageMap<T::AccountId, T::Balance>,
+	// The total amount of tokens in existence, tracked through minting and burning.
+	total_issuance: T::Balance,
+	// A storage mapping from accounts to their reserved balances, which cannot be spent.
+	reserved: StorageMap<T::AccountId, T::Balance>,
+	// The accounts which are not allowed to send funds.
+	frozen: BTreeSet<T::AccountId>,
+	// The accounts which were created explicitly, and so can receive transfers even though they
+	// have never had a stored balance.
+	created: BTreeSet<T::AccountId>,
+	// A storage mapping from accounts to their locked balance, and the block number until which it
+	// stays locked.
+	locks: StorageMap<T::AccountId, (T::Balance, T::BlockNumber)>,
+	// A storage mapping from accounts to the funds they have requested to unlock from their lock,
+	// with the block from which each of them can be withdrawn.
+	#[allow(clippy::type_complexity)]
+	unlocking: StorageMap<T::AccountId, Vec<(T::Balance, T::BlockNumber)>>,
+	// A storage mapping from an `(owner, spender)` pair to the amount the spender is allowed to
+	// transfer on behalf of the owner.
+	allowances: StorageMap<(T::AccountId, T::AccountId), T::Balance>,
+	// A storage mapping from accounts to the block number at which they last got a drip from the
+	// faucet.
+	last_drip: StorageMap<T::AccountId, T::BlockNumber>,
+	// The current block number, which is noted at the start of each block.
+	block_number: T::BlockNumber,
+	// The events emitted by this module which have not yet been collected by the runtime.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	events: Vec<Event<T>>,
+	// The accounts which were created or reaped, which have not yet been collected by the runtime.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	account_changes: Vec<AccountChange<T::AccountId>>,
+	// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
 }
 
 impl<T: Config> Pallet<T> {
 	/// Create a new instance of the balances module.
 	pub fn new() -> Self {
-		Self { balances: BTreeMap::new() }
+		Self {
+			balances: StorageMap::new(),
+			total_issuance: T::Balance::zero(),
+			reserved: StorageMap::new(),
+			frozen: BTreeSet::new(),
+			created: BTreeSet::new(),
+			locks: StorageMap::new(),
+			unlocking: StorageMap::new(),
+			allowances: StorageMap::new(),
+			last_drip: StorageMap::new(),
+			block_number: T::BlockNumber::zero(),
+			events: Vec::new(),
+			account_changes: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
 	}
 
 	/// Set the balance of an account `who` to some `amount`.
+	#[cfg(test)]
 	pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
-		self.balances.insert(who.clone(), amount);
+		self.set_balance_returning(who, amount);
+	}
+
+	/// Set the balance of an account `who` to some `amount`, returning the balance it had before.
+	/// If the account had no stored balance, we return the initial balance.
+	#[cfg(test)]
+	pub fn set_balance_returning(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		let previous = self.insert_balance(who, amount).unwrap_or_else(T::initial_balance);
+		self.deposit_event(Event::BalanceSet { who: who.clone(), free: amount });
+		previous
+	}
+
+	/// Remove the stored balance of `who` entirely, returning the amount which was removed, or zero
+	/// if the account had no stored balance. The removed amount is taken out of the total issuance.
+	/// Unlike setting the balance to zero, this leaves no entry for the account behind.
+	#[cfg(test)]
+	pub fn clear_balance(&mut self, who: &T::AccountId) -> T::Balance {
+		let amount = self.remove_balance(who).unwrap_or_else(T::Balance::zero);
+		self.total_issuance = self.total_issuance.saturating_sub(amount);
+		amount
 	}
 
 	/// Get the balance of an account `who`.
-	/// If the account has no stored balance, we return zero.
+	/// If the account has no stored balance, we return the initial balance, which is zero unless
+	/// configured otherwise.
 	pub fn balance(&self, who: &T::AccountId) -> T::Balance {
-		*self.balances.get(who).unwrap_or(&T::Balance::zero())
+		self.balances.get(who).copied().unwrap_or_else(T::initial_balance)
+	}
+
+	/// Render the balance of `who` in whole tokens, with the decimals and symbol of the token, for
+	/// example `123.45 SYM`.
+	pub fn format_balance(&self, who: &T::AccountId) -> String {
+		let decimals = T::DECIMALS as usize;
+		let units = self.balance(who).to_string();
+		if decimals == 0 {
+			return format!("{} {}", units, T::symbol());
+		}
+		// Pad with leading zeros, so there is at least one digit before the decimal point.
+		let units = format!("{:0>width$}", units, width = decimals + 1);
+		let (whole, fraction) = units.split_at(units.len() - decimals);
+		format!("{}.{} {}", whole, fraction, T::symbol())
+	}
+
+	/// Iterate over all the accounts which have a stored balance, in sorted account order.
+	pub fn accounts(&self) -> impl Iterator<Item = (&T::AccountId, &T::Balance)> {
+		self.balances.iter()
+	}
+
+	/// Get all the accounts which have a stored balance, with their balance, in sorted account
+	/// order.
+	#[cfg(test)]
+	pub fn all_balances(&self) -> Vec<(T::AccountId, T::Balance)> {
+		self.accounts().map(|(who, amount)| (who.clone(), *amount)).collect()
+	}
+
+	/// Get the total amount of tokens in existence.
+	pub fn total_issuance(&self) -> T::Balance {
+		self.total_issuance
+	}
+
+	/// Create `amount` new tokens and add them to the balance of `who`.
+	/// This function verifies that neither the account balance nor the total issuance overflow.
+	pub fn mint(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_balance = self.balance(who).checked_add(&amount).ok_or(DispatchError::Overflow)?;
+		let new_total_issuance =
+			self.total_issuance.checked_add(&amount).ok_or(DispatchError::Overflow)?;
+
+		self.insert_balance(who, new_balance);
+		self.total_issuance = new_total_issuance;
+
+		Ok(())
+	}
+
+	/// Deposit `amount` new tokens into the balance of `who`, creating the account if needed.
+	/// This function will return an error if the account does not exist yet and `amount` is below
+	/// the minimum balance, since the account would be reaped right away.
+	#[cfg(test)]
+	pub fn deposit_creating(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		if !self.balances.contains_key(who) && (amount.is_zero() || amount < T::minimum_balance()) {
+			return Err(DispatchError::ExistentialDeposit);
+		}
+		self.mint(who, amount)
+	}
+
+	/// Check whether `amount` could be taken out of the balance of `who`, without changing any
+	/// state: `who` must not be frozen, and must have `amount` balance which is not locked.
+	pub fn ensure_can_withdraw(&self, who: &T::AccountId, amount: &T::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		if self.frozen.contains(who) {
+			return Err(DispatchError::AccountFrozen);
+		}
+		let remaining = self
+			.balance(who)
+			.checked_sub(amount)
+			.ok_or(DispatchError::InsufficientBalance)?;
+		if remaining < self.locked_balance(who) {
+			return Err(DispatchError::FundsLocked);
+		}
+		Ok(())
+	}
+
+	/// Withdraw `amount` tokens from the balance of `who`, removing them from the total issuance.
+	/// If the remaining balance is below the minimum balance, the account is reaped.
+	/// This function will return an error if `who` is frozen, or does not have enough funds which
+	/// are not locked.
+	pub fn withdraw(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		self.ensure_can_withdraw(who, &amount)?;
+		let new_balance = self.balance(who).saturating_sub(amount);
+
+		// Balances written directly with `set_balance` are not part of the total issuance, so we
+		// never let it go below zero.
+		self.total_issuance =
+			self.total_issuance.checked_sub(&amount).unwrap_or(T::Balance::zero());
+		self.set_balance_or_reap(who, new_balance);
+
+		Ok(())
+	}
+
+	/// Take `amount` out of the balance of `from` on behalf of `to`, with the same checks and
+	/// transfer fee as `transfer`, but removing it from the total issuance instead of paying it to
+	/// `to`, for example to hold it in a vesting schedule for `to`.
+	pub fn withdraw_transfer(
+		&mut self,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.can_transfer(from, to, &amount)?;
+		self.deduct_fee(from, Self::transfer_fee(&amount))?;
+		self.withdraw(from, amount)
+	}
+
+	/// Mint the drip amount of the faucet to `who`, at block `now`.
+	/// This function will return an error if `who` got a drip within the cooldown blocks before
+	/// `now`, or if the mint overflows.
+	pub fn faucet(&mut self, who: &T::AccountId, now: T::BlockNumber) -> DispatchResult {
+		if let Some(last_drip) = self.last_drip.get(who) {
+			// A cooldown which ends past the largest block number never ends.
+			let cooled_down =
+				last_drip.checked_add(&T::cooldown_blocks()).is_some_and(|until| until <= now);
+			if !cooled_down {
+				return Err(DispatchError::FaucetCooldown);
+			}
+		}
+		self.mint(who, T::drip_amount())?;
+		self.last_drip.insert(who.clone(), now);
+		Ok(())
+	}
+
+	/// Take the `fee` for a call from the balance of `who`, and move it to the fee destination, or
+	/// burn it if there is none.
+	/// This function will return an error if `who` cannot afford the fee.
+	pub fn deduct_fee(&mut self, who: &T::AccountId, fee: T::Balance) -> DispatchResult {
+		if fee.is_zero() {
+			return Ok(());
+		}
+		self.withdraw(who, fee)?;
+		match T::fee_destination() {
+			Some(destination) => self.mint(&destination, fee),
+			None => Ok(()),
+		}
+	}
+
+	/// Get the reserved balance of an account `who`.
+	/// If the account has no reserved balance, we return zero.
+	pub fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+		self.reserved.get_or_default(who)
+	}
+
+	/// Iterate over all the accounts which have a reserved balance, with their reserved balance,
+	/// in sorted account order.
+	pub fn reserved_accounts(&self) -> impl Iterator<Item = (&T::AccountId, &T::Balance)> {
+		self.reserved.iter()
+	}
+
+	/// Move `amount` from the free balance of `who` into its reserved balance.
+	/// This function verifies that `who` has at least `amount` free balance to reserve.
+	/// Reserved funds cannot be spent until they are unreserved.
+	#[cfg(test)]
+	pub fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		let new_free = self
+			.balance(who)
+			.checked_sub(&amount)
+			.ok_or(DispatchError::InsufficientBalance)?;
+		let new_reserved =
+			self.reserved_balance(who).checked_add(&amount).ok_or(DispatchError::Overflow)?;
+
+		self.insert_balance(who, new_free);
+		self.reserved.insert(who.clone(), new_reserved);
+
+		Ok(())
+	}
+
+	/// Move up to `amount` from the reserved balance of `who` back into its free balance.
+	/// Returns the part of `amount` which could not be unreserved.
+	#[cfg(test)]
+	pub fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		let reserved = self.reserved_balance(who);
+		let actual = if amount < reserved { amount } else { reserved };
+
+		let new_free = match self.balance(who).checked_add(&actual) {
+			Some(new_free) => new_free,
+			None => return amount,
+		};
+		let new_reserved = reserved.checked_sub(&actual).unwrap_or(T::Balance::zero());
+
+		self.insert_balance(who, new_free);
+		if new_reserved.is_zero() {
+			self.reserved.remove(who);
+		} else {
+			self.reserved.insert(who.clone(), new_reserved);
+		}
+
+		amount.checked_sub(&actual).unwrap_or(T::Balance::zero())
+	}
+
+	/// Confiscate up to `amount` from `who`: first from its free balance, then from its reserved
+	/// balance. The slashed funds are burned, and removed from the total issuance.
+	/// Returns the amount which was slashed, and the part of `amount` which could not be.
+	#[cfg(test)]
+	pub fn slash(&mut self, who: &T::AccountId, amount: T::Balance) -> (T::Balance, T::Balance) {
+		let free = self.balance(who);
+		let from_free = if amount < free { amount } else { free };
+		let reserved = self.reserved_balance(who);
+		let remaining = amount.saturating_sub(from_free);
+		let from_reserved = if remaining < reserved { remaining } else { reserved };
+
+		if !from_free.is_zero() {
+			self.set_balance_or_reap(who, free.saturating_sub(from_free));
+		}
+		let new_reserved = reserved.saturating_sub(from_reserved);
+		if new_reserved.is_zero() {
+			self.reserved.remove(who);
+		} else {
+			self.reserved.insert(who.clone(), new_reserved);
+		}
+
+		let slashed = from_free.saturating_add(from_reserved);
+		self.total_issuance = self.total_issuance.saturating_sub(slashed);
+		(slashed, amount.saturating_sub(slashed))
+	}
+
+	// Update the balance of `who` after funds were taken from it.
+	// If the remaining balance is zero or below the minimum balance, the account is reaped: its
+	// entry is removed from storage and any remaining dust is burned. If accounts have a non-zero
+	// initial balance, a zero balance is kept in storage instead, so a reaped account does not get
+	// the initial balance again.
+	fn set_balance_or_reap(&mut self, who: &T::AccountId, amount: T::Balance) {
+		if amount.is_zero() || amount < T::minimum_balance() {
+			if T::initial_balance().is_zero() {
+				self.remove_balance(who);
+			} else {
+				self.insert_balance(who, T::Balance::zero());
+			}
+			self.total_issuance =
+				self.total_issuance.checked_sub(&amount).unwrap_or(T::Balance::zero());
+		} else {
+			self.insert_balance(who, amount);
+		}
+	}
+
+	/// Note that the account `who` was created explicitly, so it can receive transfers even when
+	/// transfers require accounts to be created.
+	pub fn note_created_account(&mut self, who: &T::AccountId) {
+		self.created.insert(who.clone());
+	}
+
+	/// Check whether the account `who` exists, because it has a stored balance, or was created
+	/// explicitly.
+	pub fn account_exists(&self, who: &T::AccountId) -> bool {
+		self.balances.contains_key(who) || self.created.contains(who)
+	}
+
+	/// Freeze the account `who`, preventing it from sending funds.
+	/// A frozen account can still receive funds.
+	#[cfg(test)]
+	pub fn freeze(&mut self, who: &T::AccountId) {
+		self.frozen.insert(who.clone());
+	}
+
+	/// Unfreeze the account `who`, allowing it to send funds again.
+	#[cfg(test)]
+	pub fn unfreeze(&mut self, who: &T::AccountId) {
+		self.frozen.remove(who);
+	}
+
+	/// Lock `amount` of the balance of `who` until the block `until_block`.
+	/// Locked funds cannot be transferred before that block. This replaces any existing lock.
+	#[cfg(test)]
+	pub fn lock(&mut self, who: &T::AccountId, amount: T::Balance, until_block: T::BlockNumber) {
+		self.locks.insert(who.clone(), (amount, until_block));
+	}
+
+	/// Get the lock (if any) of `who`, as the locked amount and the block number until which it
+	/// stays locked.
+	#[cfg(test)]
+	pub fn lock_of(&self, who: &T::AccountId) -> Option<(T::Balance, T::BlockNumber)> {
+		self.locks.get(who).copied()
+	}
+
+	/// Get the balance of `who` which is currently locked, including the funds requested to be
+	/// unlocked which have not been withdrawn yet.
+	/// Locks which have reached their unlock block are ignored.
+	pub fn locked_balance(&self, who: &T::AccountId) -> T::Balance {
+		let locked = match self.locks.get(who) {
+			Some((amount, until_block)) if self.block_number < *until_block => *amount,
+			_ => T::Balance::zero(),
+		};
+		self.unlocking(who)
+			.iter()
+			.fold(locked, |locked, (amount, _)| locked.saturating_add(*amount))
+	}
+
+	/// Get the funds `who` has requested to unlock, with the block from which each of them can be
+	/// withdrawn.
+	pub fn unlocking(&self, who: &T::AccountId) -> &[(T::Balance, T::BlockNumber)] {
+		self.unlocking.get(who).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// Request to unlock `amount` of the lock of `who`, at block `now`. The funds are taken out of
+	/// the lock, but stay locked for the bonding period, until they are withdrawn with
+	/// `withdraw_unlocked`.
+	/// This function will return an error if `amount` is zero, if the lock of `who` is less than
+	/// `amount`, or if the end of the bonding period overflows.
+	#[cfg(test)]
+	pub fn unlock_request(
+		&mut self,
+		who: &T::AccountId,
+		amount: T::Balance,
+		now: T::BlockNumber,
+	) -> DispatchResult {
+		// A request for nothing would leave an empty entry among the unlocking funds.
+		if amount.is_zero() {
+			return Err(DispatchError::InsufficientBalance);
+		}
+		let (locked, until_block) = self.lock_of(who).unwrap_or((T::Balance::zero(), now));
+		let remaining = locked.checked_sub(&amount).ok_or(DispatchError::InsufficientBalance)?;
+		let unlock_at = now
+			.checked_add(&T::bonding_period())
+			.ok_or(DispatchError::BlockNumberOverflow)?;
+
+		if remaining.is_zero() {
+			self.locks.remove(who);
+		} else {
+			self.locks.insert(who.clone(), (remaining, until_block));
+		}
+		let mut unlocking = self.unlocking.get(who).cloned().unwrap_or_default();
+		unlocking.push((amount, unlock_at));
+		self.unlocking.insert(who.clone(), unlocking);
+		Ok(())
+	}
+
+	/// Release the funds `who` has requested to unlock whose bonding period has elapsed at block
+	/// `now`, so they can be spent again.
+	/// This function will return an error if none of the funds of `who` have unlocked yet.
+	#[cfg(test)]
+	pub fn withdraw_unlocked(&mut self, who: &T::AccountId, now: T::BlockNumber) -> DispatchResult {
+		let (unlocked, pending): (Vec<_>, Vec<_>) =
+			self.unlocking(who).iter().partition(|(_, unlock_at)| *unlock_at <= now);
+		if unlocked.is_empty() {
+			return Err(DispatchError::NothingUnlocked);
+		}
+		if pending.is_empty() {
+			self.unlocking.remove(who);
+		} else {
+			self.unlocking.insert(who.clone(), pending);
+		}
+		Ok(())
+	}
+
+	/// Get the composition of the balance of `who`: their free, reserved, and currently locked
+	/// balance.
+	pub fn account_data(&self, who: &T::AccountId) -> AccountData<T::Balance> {
+		AccountData {
+			free: self.balance(who),
+			reserved: self.reserved_balance(who),
+			frozen: self.locked_balance(who),
+		}
+	}
+
+	/// Get the amount the `spender` is allowed to transfer on behalf of the `owner`.
+	pub fn allowance(&self, owner: &T::AccountId, spender: &T::AccountId) -> T::Balance {
+		self.allowances.get_or_default(&(owner.clone(), spender.clone()))
+	}
+
+	/// Check whether `who` could pay the `fee` for a call, without changing any state.
+	pub fn can_deduct_fee(&self, who: &T::AccountId, fee: &T::Balance) -> DispatchResult {
+		self.ensure_can_withdraw(who, fee)
+	}
+
+	/// Check whether the `caller` could transfer `amount` to another account, without changing any
+	/// state.
+	pub fn can_transfer(
+		&self,
+		caller: &T::AccountId,
+		to: &T::AccountId,
+		amount: &T::Balance,
+	) -> DispatchResult {
+		if T::max_transfer().is_some_and(|max| *amount > max) {
+			return Err(DispatchError::TransferExceedsLimit);
+		}
+		let caller_balance = self.balance(caller);
+		if caller == to {
+			if *amount > caller_balance {
+				return Err(DispatchError::InsufficientBalance);
+			}
+			return Ok(());
+		}
+		if self.frozen.contains(caller) {
+			return Err(DispatchError::AccountFrozen);
+		}
+		if T::require_account_creation() && !self.account_exists(to) {
+			return Err(DispatchError::AccountNotFound);
+		}
+		let total =
+			amount.checked_add(&Self::transfer_fee(amount)).ok_or(DispatchError::Overflow)?;
+		let remaining =
+			caller_balance.checked_sub(&total).ok_or(DispatchError::InsufficientBalance)?;
+		if remaining < self.locked_balance(caller) {
+			return Err(DispatchError::FundsLocked);
+		}
+		self.balance(to).checked_add(amount).ok_or(DispatchError::Overflow)?;
+		Ok(())
+	}
+
+	/// Check whether the `caller` could transfer `amount` with a `memo`, like `can_transfer`.
+	pub fn can_transfer_with_memo(
+		&self,
+		caller: &T::AccountId,
+		to: &T::AccountId,
+		amount: &T::Balance,
+		memo: &[u8],
+	) -> DispatchResult {
+		if memo.len() > T::max_memo_len() {
+			return Err(DispatchError::MemoTooLong);
+		}
+		self.can_transfer(caller, to, amount)
+	}
+
+	/// Check whether the `caller` could transfer `amount` without being reaped, like
+	/// `can_transfer`.
+	pub fn can_transfer_keep_alive(
+		&self,
+		caller: &T::AccountId,
+		to: &T::AccountId,
+		amount: &T::Balance,
+	) -> DispatchResult {
+		if caller != to {
+			let remaining = amount
+				.checked_add(&Self::transfer_fee(amount))
+				.and_then(|total| self.balance(caller).checked_sub(&total));
+			if let Some(remaining) = remaining {
+				if remaining.is_zero() || remaining < T::minimum_balance() {
+					return Err(DispatchError::WouldKillAccount);
+				}
+			}
+		}
+		self.can_transfer(caller, to, amount)
+	}
+
+	/// Check whether the `caller` could transfer their entire balance to another account.
+	pub fn can_transfer_all(&self, caller: &T::AccountId, to: &T::AccountId) -> DispatchResult {
+		let amount = self.transferable_balance(caller);
+		if amount.is_zero() || caller == to {
+			return Ok(());
+		}
+		self.can_transfer(caller, to, &amount)
+	}
+
+	/// Check whether the `caller` could transfer up to `amount` to another account. The amount is
+	/// clamped, so this only fails if the sender is frozen, or if `to` does not exist and transfers
+	/// require accounts to be created.
+	pub fn can_transfer_saturating(
+		&self,
+		caller: &T::AccountId,
+		to: &T::AccountId,
+		_amount: &T::Balance,
+	) -> DispatchResult {
+		if caller == to {
+			return Ok(());
+		}
+		if self.frozen.contains(caller) {
+			return Err(DispatchError::AccountFrozen);
+		}
+		if T::require_account_creation() && !self.account_exists(to) {
+			return Err(DispatchError::AccountNotFound);
+		}
+		Ok(())
+	}
+
+	/// Check whether the `caller` could apply all of the `transfers`: each of them must pass
+	/// `can_transfer` on its own, and the caller must be able to pay for all of them together,
+	/// fees included.
+	pub fn can_batch_transfer(
+		&self,
+		caller: &T::AccountId,
+		transfers: &[(T::AccountId, T::Balance)],
+	) -> DispatchResult {
+		let mut total = T::Balance::zero();
+		for (to, amount) in transfers {
+			self.can_transfer(caller, to, amount)?;
+			if caller != to {
+				total = total
+					.checked_add(amount)
+					.and_then(|total| total.checked_add(&Self::transfer_fee(amount)))
+					.ok_or(DispatchError::Overflow)?;
+			}
+		}
+		self.ensure_can_withdraw(caller, &total)
+	}
+
+	/// Check whether the `caller` could transfer `amount` from the `owner` to another account,
+	/// like `can_transfer`, using the allowance of the `owner`.
+	pub fn can_transfer_from(
+		&self,
+		caller: &T::AccountId,
+		owner: &T::AccountId,
+		to: &T::AccountId,
+		amount: &T::Balance,
+	) -> DispatchResult {
+		if self.allowance(owner, caller) < *amount {
+			return Err(DispatchError::InsufficientAllowance);
+		}
+		self.can_transfer(owner, to, amount)
+	}
+
+	/// Check whether the `caller` could burn `amount` of their balance, without changing any
+	/// state.
+	pub fn can_burn(&self, caller: &T::AccountId, amount: &T::Balance) -> DispatchResult {
+		let new_balance = self
+			.balance(caller)
+			.checked_sub(amount)
+			.ok_or(DispatchError::InsufficientBalance)?;
+		let burns_all = new_balance.is_zero() && T::allow_burn_to_zero();
+		if new_balance < T::reserve_floor() && !burns_all {
+			return Err(DispatchError::BelowReserveFloor);
+		}
+		self.ensure_can_withdraw(caller, amount)
+	}
+
+	/// The amount `who` can send with `transfer_all`: their entire balance, keeping back what is
+	/// needed to pay the transfer fee.
+	fn transferable_balance(&self, who: &T::AccountId) -> T::Balance {
+		// The fee on the balance is at least the fee on the amount left after taking it out.
+		let balance = self.balance(who);
+		balance.saturating_sub(Self::transfer_fee(&balance))
+	}
+
+	/// The fee the sender of a transfer of `amount` pays on top of it.
+	pub fn transfer_fee(amount: &T::Balance) -> T::Balance {
+		T::transfer_fee().mul_floor(*amount)
+	}
+
+	/// Transfer `amount` from an arbitrary account `from` to another account.
+	/// This is a privileged operation: it is not exposed as a `Call`, and only the runtime itself
+	/// should use it. Unlike `transfer`, it also moves funds out of frozen accounts.
+	#[cfg(test)]
+	pub fn force_transfer(
+		&mut self,
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.do_transfer(from, to, amount)
+	}
+
+	// Move `amount` from one account to another, verifying that `from` has at least `amount`
+	// balance to transfer, and that no mathematical overflows occur.
+	fn do_transfer(
+		&mut self,
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let from_balance = self.balance(&from);
+		let to_balance = self.balance(&to);
+
+		let new_from_balance =
+			from_balance.checked_sub(&amount).ok_or(DispatchError::InsufficientBalance)?;
+		let new_to_balance = to_balance.checked_add(&amount).ok_or(DispatchError::Overflow)?;
+
+		self.set_balance_or_reap(&from, new_from_balance);
+		self.insert_balance(&to, new_to_balance);
+
+		let (from_balance_after, to_balance_after) = (self.balance(&from), self.balance(&to));
+		self.deposit_event(Event::Transferred {
+			from,
+			to,
+			amount,
+			from_balance_after,
+			to_balance_after,
+		});
+
+		Ok(())
+	}
+
+	/// Get the events emitted by this module which have not yet been collected by the runtime.
+	#[cfg(test)]
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	// Buffer an event emitted by this module.
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	/// Take the accounts which were created or reaped since this was last called, in the order
+	/// it happened. The runtime calls this to keep the accounts of the system pallet up to date.
+	pub fn take_account_changes(&mut self) -> Vec<AccountChange<T::AccountId>> {
+		core::mem::take(&mut self.account_changes)
+	}
+
+	// Store the balance of `who`, noting that the account is created if it had no stored balance.
+	fn insert_balance(&mut self, who: &T::AccountId, amount: T::Balance) -> Option<T::Balance> {
+		let previous = self.balances.insert(who.clone(), amount);
+		if previous.is_none() {
+			self.account_changes.push(AccountChange::Created(who.clone()));
+		}
+		previous
+	}
+
+	// Remove the stored balance of `who`, noting that the account is reaped if it had one.
+	fn remove_balance(&mut self, who: &T::AccountId) -> Option<T::Balance> {
+		let previous = self.balances.remove(who);
+		if previous.is_some() {
+			self.account_changes.push(AccountChange::Reaped(who.clone()));
+		}
+		previous
+	}
+}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+	fn on_initialize(&mut self, n: T::BlockNumber) {
+		self.block_number = n;
+
+		// The reward is not minted if it would overflow, since the block cannot fail to start.
+		let reward = T::block_reward();
+		if let Some(author) = T::author().filter(|_| !reward.is_zero()) {
+			let _ = self.mint(&author, reward);
+		}
+	}
+
+	// The locks which have reached their unlock block are ignored, so they are removed at the end
+	// of the block.
+	fn on_finalize(&mut self, n: T::BlockNumber) {
+		self.locks.retain(|_, (_, until_block)| *until_block > n);
+	}
+}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config, E: From<Event<T>>> TakeEvents<E> for Pallet<T> {
+	fn take_events(&mut self) -> Vec<E> {
+		self.events.drain(..).map(Into::into).collect()
 	}
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
 	/// Transfer `amount` from one account to another.
-	/// This function verifies that `from` has at least `amount` balance to transfer,
-	/// and that no mathematical overflows occur.
+	/// This function verifies that `from` is not frozen, has at least `amount` balance to transfer
+	/// which is not locked, and that no mathematical overflows occur.
+	/// If the remaining balance of `from` falls below the minimum balance, the account is reaped.
+	/// The sender also pays the transfer fee, if there is one, on top of `amount`.
+	/// A transfer to the sender itself changes nothing, but still fails if the sender does not
+	/// have `amount` balance.
+	#[validate(can_transfer)]
 	pub fn transfer(
 		&mut self,
 		caller: T::AccountId,
 		to: T::AccountId,
 		amount: T::Balance,
-	) -> crate::support::DispatchResult {
+	) -> DispatchResult {
+		self.can_transfer(&caller, &to, &amount)?;
+		if caller == to {
+			return Ok(());
+		}
+
+		self.deduct_fee(&caller, Self::transfer_fee(&amount))?;
+		self.do_transfer(caller, to, amount)
+	}
+
+	/// Transfer `amount` from one account to another, like `transfer`, attaching a `memo` to it.
+	/// The memo is not stored, but recorded in an event.
+	/// This function will return an error if the memo is longer than the maximum memo length.
+	#[validate(can_transfer_with_memo)]
+	pub fn transfer_with_memo(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+		memo: Vec<u8>,
+	) -> DispatchResult {
+		if memo.len() > T::max_memo_len() {
+			return Err(DispatchError::MemoTooLong);
+		}
+		self.transfer(caller.clone(), to.clone(), amount)?;
+		self.deposit_event(Event::TransferredWithMemo { from: caller, to, amount, memo });
+		Ok(())
+	}
+
+	/// Transfer `amount` from one account to another, like `transfer`, but without reaping the
+	/// account of the sender.
+	/// This function will return an error if the sender would be left with less than the minimum
+	/// balance, after paying the transfer fee.
+	#[validate(can_transfer_keep_alive)]
+	pub fn transfer_keep_alive(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.can_transfer_keep_alive(&caller, &to, &amount)?;
+		self.transfer(caller, to, amount)
+	}
+
+	/// Transfer the entire balance of `caller` to another account, keeping back what is needed to
+	/// pay the transfer fee.
+	/// If `caller` has no balance, or is sending to themselves, this does nothing.
+	#[validate(can_transfer_all)]
+	pub fn transfer_all(&mut self, caller: T::AccountId, to: T::AccountId) -> DispatchResult {
+		let amount = self.transferable_balance(&caller);
+		if amount.is_zero() || caller == to {
+			return Ok(());
+		}
+
+		self.transfer(caller, to, amount)
+	}
+
+	/// Transfer up to `amount` from the `caller` to another account, clamping instead of failing.
+	/// At most the maximum transfer is sent, and at most what the unlocked balance of the caller
+	/// covers along with the transfer fee, which the caller pays like for `transfer`. The balance
+	/// of `to` is capped at the largest possible balance: the excess is burned, and removed from
+	/// the total issuance.
+	/// This function still returns an error if the sender is frozen, or if `to` does not exist and
+	/// transfers require accounts to be created.
+	#[validate(can_transfer_saturating)]
+	pub fn transfer_saturating(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		self.can_transfer_saturating(&caller, &to, &amount)?;
+		if caller == to {
+			return Ok(());
+		}
+		let unlocked = self.balance(&caller).saturating_sub(self.locked_balance(&caller));
+		let amount = match T::max_transfer() {
+			Some(max) if amount > max => max,
+			_ => amount,
+		};
+		// The fee on the unlocked balance is at least the fee on the amount left after taking it
+		// out, like in `transfer_all`.
+		let amount = match amount.checked_add(&Self::transfer_fee(&amount)) {
+			Some(total) if total <= unlocked => amount,
+			_ => unlocked.saturating_sub(Self::transfer_fee(&unlocked)),
+		};
+		if amount.is_zero() {
+			return Ok(());
+		}
+
+		self.deduct_fee(&caller, Self::transfer_fee(&amount))?;
 		let caller_balance = self.balance(&caller);
 		let to_balance = self.balance(&to);
+		let new_to_balance = to_balance.saturating_add(amount);
+		let received = new_to_balance.saturating_sub(to_balance);
 
-		let new_caller_balance = caller_balance.checked_sub(&amount).ok_or("Not enough funds.")?;
-		let new_to_balance = to_balance.checked_add(&amount).ok_or("Funds exceed limit.")?;
+		self.set_balance_or_reap(&caller, caller_balance.saturating_sub(amount));
+		self.insert_balance(&to, new_to_balance);
+		self.total_issuance = self.total_issuance.saturating_sub(amount.saturating_sub(received));
 
-		self.set_balance(&caller, new_caller_balance);
-		self.set_balance(&to, new_to_balance);
+		self.deposit_event(Event::Transferred {
+			from_balance_after: self.balance(&caller),
+			to_balance_after: new_to_balance,
+			from: caller,
+			to,
+			amount: received,
+		});
 
 		Ok(())
 	}
-}
 
-#[cfg(test)]
-mod tests {
-	struct TestConfig;
+	/// Apply a batch of `transfers` from the `caller`, in order.
+	/// This stops at the first transfer which fails and returns its error. Note that this is not
+	/// atomic: the transfers which succeeded before the failure are not rolled back.
+	#[weight(10)]
+	#[validate(can_batch_transfer)]
+	pub fn batch_transfer(
+		&mut self,
+		caller: T::AccountId,
+		transfers: Vec<(T::AccountId, T::Balance)>,
+	) -> DispatchResult {
+		for (to, amount) in transfers {
+			self.transfer(caller.clone(), to, amount)?;
+		}
 
-	impl crate::system::Config for TestConfig {
-		type AccountId = &'static str;
-		type BlockNumber = u32;
-		type Nonce = u32;
+		Ok(())
 	}
 
-	impl super::Config for TestConfig {
-		type Balance = u128;
+	/// Apply a batch of `transfers` from the `caller` atomically.
+	/// The transfers are applied in order, like `batch_transfer`, with the same checks and fees,
+	/// but if one of them fails, the changes of the ones before it are rolled back, so either all
+	/// of the transfers are applied, or none of them are and the error of the first failing
+	/// transfer is returned.
+	#[weight(10)]
+	#[validate(can_batch_transfer)]
+	pub fn batch_transfer_atomic(
+		&mut self,
+		caller: T::AccountId,
+		transfers: Vec<(T::AccountId, T::Balance)>,
+	) -> DispatchResult {
+		if self.frozen.contains(&caller) && !transfers.is_empty() {
+			return Err(DispatchError::AccountFrozen);
+		}
+
+		// A transfer only changes the stored balances, the total issuance, and the events and
+		// account changes buffered since, so those are all we need to restore.
+		let balances = self.balances.clone();
+		let total_issuance = self.total_issuance;
+		let (events, account_changes) = (self.events.len(), self.account_changes.len());
+
+		let result = self.batch_transfer(caller, transfers);
+		if result.is_err() {
+			self.balances = balances;
+			self.total_issuance = total_issuance;
+			self.events.truncate(events);
+			self.account_changes.truncate(account_changes);
+		}
+		result
 	}
 
-	#[test]
-	fn init_balances() {
-		let mut balances = super::Pallet::<TestConfig>::new();
+	/// Allow the `spender` to transfer up to `amount` on behalf of the `caller`.
+	/// This replaces any existing allowance of the spender.
+	pub fn approve(
+		&mut self,
+		caller: T::AccountId,
+		spender: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			self.allowances.remove(&(caller, spender));
+		} else {
+			self.allowances.insert((caller, spender), amount);
+		}
+		Ok(())
+	}
 
-		assert_eq!(balances.balance(&"alice"), 0);
+	/// Transfer `amount` from the `owner` to another account, on behalf of the `caller`.
+	/// This function verifies that the `caller` is allowed to transfer at least `amount` for the
+	/// `owner`, and then applies the same checks as `transfer`. The allowance is reduced by the
+	/// transferred amount, unless the funds are sent back to the `owner`, which moves nothing.
+	#[validate(can_transfer_from)]
+	pub fn transfer_from(
+		&mut self,
+		caller: T::AccountId,
+		owner: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		let remaining = self
+			.allowance(&owner, &caller)
+			.checked_sub(&amount)
+			.ok_or(DispatchError::InsufficientAllowance)?;
 
-		balances.set_balance(&"alice", 100);
-		assert_eq!(balances.balance(&"alice"), 100);
+		let moves_nothing = owner == to;
+		self.transfer(owner.clone(), to, amount)?;
+		if moves_nothing {
+			return Ok(());
+		}
 
-		assert_eq!(balances.balance(&"bob"), 0)
+		self.approve(owner, caller, remaining)
 	}
 
-	#[test]
-	fn transfer_balance() {
-		let mut balances = super::Pallet::<TestConfig>::new();
+	/// Burn `amount` from the balance of the `caller`, permanently destroying those tokens.
+	/// This function verifies that `caller` is not frozen, and has at least `amount` balance to
+	/// burn which is not locked.
+	/// It also verifies that the remaining balance does not fall below the reserve floor, unless
+	/// all of the balance is burned and that is allowed.
+	/// If the remaining balance falls below the minimum balance, the account is reaped.
+	#[validate(can_burn)]
+	pub fn burn(&mut self, caller: T::AccountId, amount: T::Balance) -> DispatchResult {
+		self.can_burn(&caller, &amount)?;
+		self.withdraw(&caller, amount)
+	}
+}
 
-		let alice = "alice";
-		let bob = "bob";
+#[cfg(test)]
+mod tests {
+	use crate::support::{Dispatch, DispatchError, Origin};
 
-		assert!(balances.transfer(alice, bob, 100).is_err());
+	// Define a configuration of the pallet, with the account, block number, nonce and event types
+	// of the tests, a balance of `u128`, and the given items of `balances::Config`.
+	macro_rules! test_config {
+		(struct $config:ident; $($item:tt)*) => {
+			#[derive(Debug, PartialEq, Eq)]
+			struct $config;
 
-		balances.set_balance(&alice, 100);
-		assert!(balances.transfer(alice, bob, 50).is_ok());
+			impl crate::system::Config for $config {
+				type AccountId = &'static str;
+				type BlockNumber = u32;
+				type Nonce = u32;
+				type RuntimeEvent = super::Event<Self>;
+				type Hash = u64;
+			}
 
-		assert_eq!(balances.balance(&alice), 50);
+			impl super::Config for $config {
+				type Balance = u128;
+
+				$($item)*
+			}
+		};
+	}
+
+	test_config! {
+		struct TestConfig;
+	}
+
+	// A configuration which reaps accounts holding less than 5 units.
+	test_config! {
+		struct MinimumBalanceConfig;
+
+		fn minimum_balance() -> Self::Balance {
+			5
+		}
+	}
+
+	// A configuration which pays the fees of calls to a treasury account, and rewards an author for
+	// each block.
+	test_config! {
+		struct FeeConfig;
+
+		fn fee_destination() -> Option<Self::AccountId> {
+			Some("treasury")
+		}
+
+		fn block_reward() -> Self::Balance {
+			10
+		}
+
+		fn author() -> Option<Self::AccountId> {
+			Some("author")
+		}
+	}
+
+	// A configuration where every account starts with a faucet balance of 10 units.
+	test_config! {
+		struct InitialBalanceConfig;
+
+		fn initial_balance() -> Self::Balance {
+			10
+		}
+	}
+
+	// A configuration which caps each transfer at 50 units.
+	test_config! {
+		struct MaxTransferConfig;
+
+		fn max_transfer() -> Option<Self::Balance> {
+			Some(50)
+		}
+	}
+
+	// A configuration for a token with 2 decimals.
+	test_config! {
+		struct TokenConfig;
+
+		const DECIMALS: u8 = 2;
+
+		fn symbol() -> &'static str {
+			"SYM"
+		}
+	}
+
+	// A configuration which charges 1% of the amount of each transfer, paid to a treasury account.
+	test_config! {
+		struct TransferFeeConfig;
+
+		fn fee_destination() -> Option<Self::AccountId> {
+			Some("treasury")
+		}
+
+		fn transfer_fee() -> crate::support::Perbill {
+			crate::support::Perbill::from_percent(1)
+		}
+	}
+
+	// A configuration which charges 1% of the amount of each transfer, and reaps accounts left with
+	// less than 5 units.
+	test_config! {
+		struct TransferFeeMinimumConfig;
+
+		fn minimum_balance() -> Self::Balance {
+			5
+		}
+
+		fn transfer_fee() -> crate::support::Perbill {
+			crate::support::Perbill::from_percent(1)
+		}
+	}
+
+	// A configuration which only allows transfers into accounts which exist.
+	test_config! {
+		struct AccountCreationConfig;
+
+		fn require_account_creation() -> bool {
+			true
+		}
+	}
+
+	// A configuration which keeps a reserve of 10 units that accounts cannot burn below.
+	test_config! {
+		struct ReserveFloorConfig;
+
+		fn reserve_floor() -> Self::Balance {
+			10
+		}
+	}
+
+	// A configuration in which unlocked funds stay locked for a bonding period of 5 blocks.
+	test_config! {
+		struct BondingConfig;
+
+		fn bonding_period() -> Self::BlockNumber {
+			5
+		}
+	}
+
+	// A configuration with a faucet which drips 10 units, once every 5 blocks.
+	test_config! {
+		struct FaucetConfig;
+
+		fn drip_amount() -> Self::Balance {
+			10
+		}
+
+		fn cooldown_blocks() -> Self::BlockNumber {
+			5
+		}
+	}
+
+	#[test]
+	fn init_balances() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(balances.balance(&"alice"), 0);
+
+		balances.set_balance(&"alice", 100);
+		assert_eq!(balances.balance(&"alice"), 100);
+
+		assert_eq!(balances.balance(&"bob"), 0)
+	}
+
+	#[test]
+	fn transfer_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		assert!(balances.transfer(alice, bob, 100).is_err());
+
+		balances.set_balance(&alice, 100);
+		assert!(balances.transfer(alice, bob, 50).is_ok());
+
+		assert_eq!(balances.balance(&alice), 50);
 		assert_eq!(balances.balance(&bob), 50);
 	}
+
+	#[test]
+	fn reap_account_below_minimum_balance() {
+		let mut balances = super::Pallet::<MinimumBalanceConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		assert!(balances.mint(&alice, 100).is_ok());
+
+		// Alice is left with 4 units, which is 1 unit below the minimum balance.
+		assert!(balances.transfer(alice, bob, 96).is_ok());
+		assert!(!balances.balances.contains_key(&alice));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&bob), 96);
+		// The dust of the reaped account is burned.
+		assert_eq!(balances.total_issuance(), 96);
+
+		// Leaving exactly the minimum balance keeps the account alive.
+		assert!(balances.burn(bob, 91).is_ok());
+		assert_eq!(balances.balances.get(&bob), Some(&5));
+
+		assert!(balances.burn(bob, 1).is_ok());
+		assert!(!balances.balances.contains_key(&bob));
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn reap_account_at_zero() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		balances.set_balance(&"alice", 100);
+		assert!(balances.transfer("alice", "bob", 100).is_ok());
+		assert!(!balances.balances.contains_key(&"alice"));
+		assert_eq!(balances.balance(&"bob"), 100);
+	}
+
+	#[test]
+	fn reserve_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.set_balance(&alice, 100);
+		assert_eq!(balances.reserve(&alice, 101), Err(DispatchError::InsufficientBalance));
+
+		assert!(balances.reserve(&alice, 60).is_ok());
+		assert_eq!(balances.balance(&alice), 40);
+		assert_eq!(balances.reserved_balance(&alice), 60);
+
+		// Reserved funds cannot be transferred.
+		assert_eq!(balances.transfer(alice, bob, 50), Err(DispatchError::InsufficientBalance));
+
+		assert_eq!(balances.unreserve(&alice, 20), 0);
+		assert_eq!(balances.balance(&alice), 60);
+		assert_eq!(balances.reserved_balance(&alice), 40);
+	}
+
+	#[test]
+	fn partial_unreserve() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+
+		balances.set_balance(&alice, 100);
+		assert!(balances.reserve(&alice, 30).is_ok());
+
+		// Only 30 units are reserved, so 20 of the requested 50 cannot be unreserved.
+		assert_eq!(balances.unreserve(&alice, 50), 20);
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.reserved_balance(&alice), 0);
+		assert!(!balances.reserved.contains_key(&alice));
+
+		assert_eq!(balances.unreserve(&"bob", 10), 10);
+	}
+
+	#[test]
+	fn active_lock() {
+		use crate::support::Hooks;
+
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.on_initialize(1);
+		balances.set_balance(&alice, 100);
+		balances.lock(&alice, 60, 5);
+		assert_eq!(balances.locked_balance(&alice), 60);
+
+		// Only the 40 units which are not locked can be spent.
+		assert_eq!(balances.transfer(alice, bob, 41), Err(DispatchError::FundsLocked));
+		assert!(balances.transfer(alice, bob, 40).is_ok());
+		assert_eq!(balances.transfer(alice, bob, 1), Err(DispatchError::FundsLocked));
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 1)]),
+			Err(DispatchError::FundsLocked)
+		);
+
+		balances.on_initialize(4);
+		assert_eq!(balances.transfer(alice, bob, 1), Err(DispatchError::FundsLocked));
+		assert_eq!(balances.balance(&alice), 60);
+		assert_eq!(balances.balance(&bob), 40);
+	}
+
+	#[test]
+	fn expired_lock() {
+		use crate::support::Hooks;
+
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.on_initialize(1);
+		balances.set_balance(&alice, 100);
+		balances.lock(&alice, 60, 5);
+
+		// The lock is ignored once its unlock block is reached.
+		balances.on_initialize(5);
+		assert_eq!(balances.locked_balance(&alice), 0);
+		assert!(balances.transfer(alice, bob, 100).is_ok());
+		assert_eq!(balances.balance(&bob), 100);
+
+		// ...and removed at the end of that block.
+		balances.on_finalize(4);
+		assert_eq!(balances.lock_of(&alice), Some((60, 5)));
+		balances.on_finalize(5);
+		assert_eq!(balances.lock_of(&alice), None);
+	}
+
+	#[test]
+	fn account_data() {
+		use crate::support::Hooks;
+
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		let empty = super::AccountData { free: 0, reserved: 0, frozen: 0 };
+		assert_eq!(balances.account_data(&alice), empty);
+
+		balances.on_initialize(1);
+		balances.mint(&alice, 100).unwrap();
+		balances.reserve(&alice, 30).unwrap();
+		balances.lock(&alice, 50, 5);
+		assert_eq!(
+			balances.account_data(&alice),
+			super::AccountData { free: 70, reserved: 30, frozen: 50 }
+		);
+
+		// Only the locks which are still active are counted.
+		balances.on_initialize(5);
+		assert_eq!(balances.account_data(&alice).frozen, 0);
+	}
+
+	#[test]
+	fn unlock_request() {
+		use crate::support::Hooks;
+
+		let mut balances = super::Pallet::<BondingConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.on_initialize(1);
+		balances.mint(&alice, 100).unwrap();
+		// Without a lock, there is nothing to unlock.
+		assert_eq!(balances.unlock_request(&alice, 0, 1), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.unlock_request(&alice, 1, 1), Err(DispatchError::InsufficientBalance));
+		balances.lock(&alice, 60, 100);
+		assert_eq!(balances.unlock_request(&alice, 61, 1), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.unlock_request(&alice, 0, 1), Err(DispatchError::InsufficientBalance));
+		assert!(balances.unlocking(&alice).is_empty());
+
+		// The requested funds leave the lock, but stay locked for the bonding period.
+		assert_eq!(balances.unlock_request(&alice, 20, 1), Ok(()));
+		assert_eq!(balances.lock_of(&alice), Some((40, 100)));
+		assert_eq!(balances.unlocking(&alice), &[(20, 6)]);
+		assert_eq!(balances.locked_balance(&alice), 60);
+
+		balances.on_initialize(3);
+		assert_eq!(balances.withdraw_unlocked(&alice, 3), Err(DispatchError::NothingUnlocked));
+		assert_eq!(balances.transfer(alice, bob, 41), Err(DispatchError::FundsLocked));
+
+		balances.on_initialize(6);
+		assert_eq!(balances.withdraw_unlocked(&alice, 6), Ok(()));
+		assert!(balances.unlocking(&alice).is_empty());
+		assert_eq!(balances.locked_balance(&alice), 40);
+		assert_eq!(balances.transfer(alice, bob, 60), Ok(()));
+		assert_eq!(balances.withdraw_unlocked(&alice, 7), Err(DispatchError::NothingUnlocked));
+	}
+
+	#[test]
+	fn freeze_account() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.set_balance(&alice, 100);
+		balances.set_balance(&bob, 100);
+
+		balances.freeze(&alice);
+		assert_eq!(balances.transfer(alice, bob, 50), Err(DispatchError::AccountFrozen));
+		assert_eq!(balances.balance(&alice), 100);
+
+		// A frozen account can still receive funds.
+		assert!(balances.transfer(bob, alice, 50).is_ok());
+		assert_eq!(balances.balance(&alice), 150);
+
+		balances.unfreeze(&alice);
+		assert!(balances.transfer(alice, bob, 50).is_ok());
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.balance(&bob), 100);
+	}
+
+	#[test]
+	fn force_transfer_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.set_balance(&alice, 100);
+		balances.freeze(&alice);
+
+		// The funds of alice are moved without alice being the caller, even though the account is
+		// frozen.
+		assert!(balances.force_transfer(alice, bob, 60).is_ok());
+		assert_eq!(balances.balance(&alice), 40);
+		assert_eq!(balances.balance(&bob), 60);
+
+		assert_eq!(
+			balances.force_transfer(alice, bob, 41),
+			Err(DispatchError::InsufficientBalance)
+		);
+	}
+
+	#[test]
+	fn transfer_events() {
+		use super::Event;
+		use crate::support::TakeEvents;
+
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		balances.set_balance(&"alice", 100);
+		assert!(balances.transfer("alice", "bob", 30).is_ok());
+		assert!(balances.transfer("alice", "charlie", 20).is_ok());
+		// Failed transfers do not emit an event.
+		assert!(balances.transfer("bob", "charlie", 31).is_err());
+
+		let events: Vec<Event<TestConfig>> = balances.take_events();
+		assert_eq!(
+			events,
+			vec![
+				Event::BalanceSet { who: "alice", free: 100 },
+				Event::Transferred {
+					from: "alice",
+					to: "bob",
+					amount: 30,
+					from_balance_after: 70,
+					to_balance_after: 30
+				},
+				Event::Transferred {
+					from: "alice",
+					to: "charlie",
+					amount: 20,
+					from_balance_after: 50,
+					to_balance_after: 20
+				},
+			]
+		);
+		assert!(balances.events().is_empty());
+	}
+
+	#[test]
+	fn transfer_all_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		// Nothing to transfer.
+		assert!(balances.transfer_all(alice, bob).is_ok());
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&bob), 0);
+
+		balances.set_balance(&alice, 100);
+		balances.set_balance(&bob, 10);
+		assert!(balances.transfer_all(alice, bob).is_ok());
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&bob), 110);
+
+		// Sending everything to yourself leaves the balance untouched.
+		assert!(balances.transfer_all(bob, bob).is_ok());
+		assert_eq!(balances.balance(&bob), 110);
+
+		balances.set_balance(&alice, u128::MAX);
+		assert_eq!(balances.transfer_all(bob, alice), Err(DispatchError::Overflow));
+		assert_eq!(balances.balance(&alice), u128::MAX);
+		assert_eq!(balances.balance(&bob), 110);
+
+		// The transfer fee is kept back, so it can still be paid.
+		let mut balances = super::Pallet::<TransferFeeConfig>::new();
+		balances.mint(&alice, 1_000).unwrap();
+		assert_eq!(balances.transfer_all(alice, bob), Ok(()));
+		assert_eq!(balances.balance(&bob), 990);
+		assert_eq!(balances.balance(&"treasury"), 9);
+		assert_eq!(balances.balance(&alice), 1);
+	}
+
+	#[test]
+	fn batch_transfer_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+		let charlie = "charlie";
+
+		balances.set_balance(&alice, 100);
+		assert!(balances.batch_transfer(alice, vec![(bob, 30), (charlie, 20)]).is_ok());
+		assert_eq!(balances.balance(&alice), 50);
+		assert_eq!(balances.balance(&bob), 30);
+		assert_eq!(balances.balance(&charlie), 20);
+
+		// Alice runs out of funds on the second transfer, so the third is never applied. The first
+		// transfer is not rolled back.
+		assert_eq!(
+			balances.batch_transfer(alice, vec![(bob, 40), (charlie, 20), (bob, 5)]),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.balance(&bob), 70);
+		assert_eq!(balances.balance(&charlie), 20);
+	}
+
+	#[test]
+	fn batch_transfer_atomic_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+		let charlie = "charlie";
+
+		balances.set_balance(&alice, 100);
+		assert!(balances.batch_transfer_atomic(alice, vec![(bob, 30), (charlie, 70)]).is_ok());
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&bob), 30);
+		assert_eq!(balances.balance(&charlie), 70);
+
+		// The cumulative debits are taken into account.
+		balances.set_balance(&alice, 100);
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 60), (charlie, 60)]),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+
+		// Funds received earlier in the batch count towards the recipient's overflow check.
+		balances.set_balance(&bob, u128::MAX - 10);
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 5), (bob, 6)]),
+			Err(DispatchError::Overflow)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.balance(&bob), u128::MAX - 10);
+	}
+
+	#[test]
+	fn batch_transfer_atomic_and_non_atomic() {
+		let alice = "alice";
+		let bob = "bob";
+		let charlie = "charlie";
+		// The second of these three transfers is underfunded.
+		let transfers = vec![(bob, 50), (charlie, 60), (bob, 10)];
+
+		let mut non_atomic = super::Pallet::<TestConfig>::new();
+		non_atomic.set_balance(&alice, 100);
+		assert_eq!(
+			non_atomic.batch_transfer(alice, transfers.clone()),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(non_atomic.balance(&alice), 50);
+		assert_eq!(non_atomic.balance(&bob), 50);
+		assert_eq!(non_atomic.balance(&charlie), 0);
+
+		let mut atomic = super::Pallet::<TestConfig>::new();
+		atomic.set_balance(&alice, 100);
+		assert_eq!(
+			atomic.batch_transfer_atomic(alice, transfers),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(atomic.balance(&alice), 100);
+		assert_eq!(atomic.balance(&bob), 0);
+		assert_eq!(atomic.balance(&charlie), 0);
+	}
+
+	#[test]
+	fn burn_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+
+		assert_eq!(balances.burn(alice, 10), Err(DispatchError::InsufficientBalance));
+
+		balances.set_balance(&alice, 100);
+		assert!(balances.burn(alice, 40).is_ok());
+		assert_eq!(balances.balance(&alice), 60);
+
+		assert_eq!(balances.burn(alice, 61), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.balance(&alice), 60);
+
+		// Burning exactly the full balance leaves the account at zero.
+		assert!(balances.burn(alice, 60).is_ok());
+		assert_eq!(balances.balance(&alice), 0);
+	}
+
+	#[test]
+	fn burn_locked_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		balances.mint(&alice, 100).unwrap();
+		balances.lock(&alice, 60, 10);
+
+		// Only the 40 units which are not locked can be burned.
+		assert_eq!(balances.burn(alice, 41), Err(DispatchError::FundsLocked));
+		assert_eq!(balances.burn(alice, 40), Ok(()));
+		assert_eq!(balances.balance(&alice), 60);
+		assert_eq!(balances.locked_balance(&alice), 60);
+
+		balances.freeze(&alice);
+		balances.lock(&alice, 0, 10);
+		assert_eq!(balances.burn(alice, 10), Err(DispatchError::AccountFrozen));
+		assert_eq!(balances.balance(&alice), 60);
+	}
+
+	#[test]
+	fn burn_reserve_floor() {
+		let mut balances = super::Pallet::<ReserveFloorConfig>::new();
+		let alice = "alice";
+		balances.mint(&alice, 20).unwrap();
+
+		// Burning down to 5 would go below the floor of 10.
+		assert_eq!(balances.burn(alice, 15), Err(DispatchError::BelowReserveFloor));
+		assert_eq!(balances.balance(&alice), 20);
+
+		assert_eq!(balances.burn(alice, 10), Ok(()));
+		assert_eq!(balances.balance(&alice), 10);
+
+		// Burning all the way to zero is allowed.
+		assert_eq!(balances.burn(alice, 10), Ok(()));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn mint_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		let alice = "alice";
+		let bob = "bob";
+
+		assert_eq!(balances.total_issuance(), 0);
+
+		assert!(balances.mint(&alice, 100).is_ok());
+		assert!(balances.mint(&bob, 50).is_ok());
+		assert!(balances.mint(&alice, 25).is_ok());
+		assert_eq!(balances.balance(&alice), 125);
+		assert_eq!(balances.balance(&bob), 50);
+		assert_eq!(balances.total_issuance(), 175);
+
+		// Transfers only move tokens around.
+		assert!(balances.transfer(alice, bob, 75).is_ok());
+		assert_eq!(balances.total_issuance(), 175);
+
+		assert!(balances.burn(bob, 100).is_ok());
+		assert_eq!(balances.balance(&bob), 25);
+		assert_eq!(balances.total_issuance(), 75);
+
+		assert_eq!(balances.mint(&alice, u128::MAX), Err(DispatchError::Overflow));
+		assert_eq!(balances.balance(&alice), 50);
+		assert_eq!(balances.total_issuance(), 75);
+	}
+
+	#[test]
+	fn dispatch_burn() {
+		use crate::support::{Dispatch, Origin};
+
+		let mut balances = super::Pallet::<TestConfig>::new();
+
+		balances.set_balance(&"alice", 100);
+		assert!(balances
+			.dispatch(Origin::Signed("alice"), super::Call::burn { amount: 100 })
+			.is_ok());
+		assert_eq!(balances.balance(&"alice"), 0);
+	}
+
+	#[test]
+	fn list_accounts() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		assert_eq!(balances.accounts().count(), 0);
+
+		balances.set_balance(&"charlie", 30);
+		balances.set_balance(&"alice", 10);
+		balances.set_balance(&"bob", 20);
+		// Reading the balance of an account does not store it.
+		assert_eq!(balances.balance(&"dave"), 0);
+
+		assert_eq!(
+			balances.accounts().collect::<Vec<_>>(),
+			vec![(&"alice", &10), (&"bob", &20), (&"charlie", &30)]
+		);
+		assert_eq!(balances.all_balances(), vec![("alice", 10), ("bob", 20), ("charlie", 30)]);
+	}
+
+	#[test]
+	fn deposit_creating_account() {
+		let mut balances = super::Pallet::<MinimumBalanceConfig>::new();
+
+		// A deposit below the minimum balance cannot create an account.
+		assert_eq!(balances.deposit_creating(&"alice", 4), Err(DispatchError::ExistentialDeposit));
+		assert_eq!(balances.accounts().count(), 0);
+		assert_eq!(balances.total_issuance(), 0);
+
+		assert_eq!(balances.deposit_creating(&"alice", 5), Ok(()));
+		// Once the account exists, any deposit is allowed.
+		assert_eq!(balances.deposit_creating(&"alice", 1), Ok(()));
+		assert_eq!(balances.balance(&"alice"), 6);
+		assert_eq!(balances.total_issuance(), 6);
+	}
+
+	#[test]
+	fn withdraw_and_reap() {
+		let mut balances = super::Pallet::<MinimumBalanceConfig>::new();
+		balances.deposit_creating(&"alice", 20).unwrap();
+
+		assert_eq!(balances.withdraw(&"alice", 21), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.withdraw(&"alice", 10), Ok(()));
+		assert_eq!(balances.balance(&"alice"), 10);
+		assert_eq!(balances.total_issuance(), 10);
+
+		// The remaining 3 units are below the minimum balance, so the account is reaped and the
+		// dust is burned.
+		assert_eq!(balances.withdraw(&"alice", 7), Ok(()));
+		assert_eq!(balances.accounts().count(), 0);
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn transfer_from_allowance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+		let charlie = "charlie";
+
+		balances.set_balance(&alice, 100);
+		assert_eq!(balances.allowance(&alice, &bob), 0);
+		assert_eq!(
+			balances.transfer_from(bob, alice, charlie, 1),
+			Err(DispatchError::InsufficientAllowance)
+		);
+
+		assert_eq!(balances.approve(alice, bob, 50), Ok(()));
+		assert_eq!(balances.allowance(&alice, &bob), 50);
+
+		// Spending more than the allowance fails, even though alice has enough funds.
+		assert_eq!(
+			balances.transfer_from(bob, alice, charlie, 51),
+			Err(DispatchError::InsufficientAllowance)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+
+		// A partial spend reduces the remaining allowance.
+		assert_eq!(balances.transfer_from(bob, alice, charlie, 30), Ok(()));
+		assert_eq!(balances.balance(&alice), 70);
+		assert_eq!(balances.balance(&charlie), 30);
+		assert_eq!(balances.allowance(&alice, &bob), 20);
+
+		// The allowance is only for bob.
+		assert_eq!(
+			balances.transfer_from(charlie, alice, charlie, 10),
+			Err(DispatchError::InsufficientAllowance)
+		);
+
+		// Sending the funds back to alice moves nothing, so the allowance is kept.
+		assert_eq!(balances.transfer_from(bob, alice, alice, 20), Ok(()));
+		assert_eq!(balances.balance(&alice), 70);
+		assert_eq!(balances.allowance(&alice, &bob), 20);
+	}
+
+	#[test]
+	fn self_transfer() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		balances.mint(&alice, 100).unwrap();
+
+		assert_eq!(balances.transfer(alice, alice, 101), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.transfer(alice, alice, 100), Ok(()));
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.total_issuance(), 100);
+		// Nothing moved, so nothing is reported.
+		assert!(balances.events().is_empty());
+
+		// The atomic batch treats self-transfers the same way.
+		balances.lock(&alice, 60, 5);
+		assert_eq!(balances.batch_transfer_atomic(alice, vec![(alice, 100)]), Ok(()));
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(alice, 101)]),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+	}
+
+	#[test]
+	fn set_balance_returning() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		assert_eq!(balances.set_balance_returning(&alice, 100), 0);
+		assert_eq!(balances.set_balance_returning(&alice, 40), 100);
+		assert_eq!(balances.balance(&alice), 40);
+	}
+
+	#[test]
+	fn deduct_fee() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		balances.mint(&alice, 10).unwrap();
+
+		// Without a fee destination, fees are burned.
+		assert_eq!(balances.deduct_fee(&alice, 4), Ok(()));
+		assert_eq!(balances.balance(&alice), 6);
+		assert_eq!(balances.total_issuance(), 6);
+		assert_eq!(balances.deduct_fee(&alice, 7), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.balance(&alice), 6);
+
+		let mut balances = super::Pallet::<FeeConfig>::new();
+		balances.mint(&alice, 10).unwrap();
+
+		assert_eq!(balances.deduct_fee(&alice, 4), Ok(()));
+		assert_eq!(balances.balance(&alice), 6);
+		assert_eq!(balances.balance(&"treasury"), 4);
+		assert_eq!(balances.total_issuance(), 10);
+	}
+
+	#[test]
+	fn transfer_saturating() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+		let charlie = "charlie";
+
+		balances.mint(&alice, 30).unwrap();
+		balances.set_balance(&bob, u128::MAX - 5);
+
+		// The balance of bob is capped, and the overflow is burned.
+		assert_eq!(balances.transfer_saturating(alice, bob, 10), Ok(()));
+		assert_eq!(balances.balance(&alice), 20);
+		assert_eq!(balances.balance(&bob), u128::MAX);
+		assert_eq!(balances.total_issuance(), 25);
+
+		// At most the balance of alice is sent.
+		assert_eq!(balances.transfer_saturating(alice, charlie, 50), Ok(()));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&charlie), 20);
+		assert_eq!(balances.total_issuance(), 25);
+
+		assert_eq!(
+			balances.events(),
+			&[
+				super::Event::BalanceSet { who: bob, free: u128::MAX - 5 },
+				super::Event::Transferred {
+					from: alice,
+					to: bob,
+					amount: 5,
+					from_balance_after: 20,
+					to_balance_after: u128::MAX
+				},
+				super::Event::Transferred {
+					from: alice,
+					to: charlie,
+					amount: 20,
+					from_balance_after: 0,
+					to_balance_after: 20
+				},
+			]
+		);
+
+		// The transfer fee is paid on top of the amount, which is clamped so both are covered.
+		let mut balances = super::Pallet::<TransferFeeConfig>::new();
+		balances.mint(&alice, 1_000).unwrap();
+		assert_eq!(balances.transfer_saturating(alice, bob, 500), Ok(()));
+		assert_eq!(balances.balance(&alice), 495);
+		assert_eq!(balances.balance(&bob), 500);
+		assert_eq!(balances.balance(&"treasury"), 5);
+
+		assert_eq!(balances.transfer_saturating(alice, bob, 1_000), Ok(()));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.balance(&bob), 991);
+		assert_eq!(balances.balance(&"treasury"), 9);
+		assert_eq!(balances.total_issuance(), 1_000);
+	}
+
+	#[test]
+	fn can_dispatch() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 10).unwrap();
+		let origin = Origin::Signed(alice);
+
+		let call = super::Call::transfer { to: bob, amount: 20 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.balance(&bob), 0);
+
+		let call = super::Call::transfer { to: bob, amount: 10 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Ok(()));
+		// A transfer must be signed.
+		assert_eq!(balances.can_dispatch(&Origin::None, &call), Err(DispatchError::BadOrigin));
+		assert_eq!(balances.balance(&alice), 10);
+
+		balances.lock(&alice, 5, 10);
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::FundsLocked));
+		balances.freeze(&alice);
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::AccountFrozen));
+	}
+
+	#[test]
+	fn can_dispatch_calls() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 10).unwrap();
+		let origin = Origin::Signed(alice);
+
+		let call = super::Call::burn { amount: 11 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::InsufficientBalance));
+		let call = super::Call::transfer_keep_alive { to: bob, amount: 10 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::WouldKillAccount));
+		let call = super::Call::transfer_with_memo { to: bob, amount: 1, memo: vec![0; 1_000] };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::MemoTooLong));
+		// Each transfer of the batch is affordable, but not all of them together.
+		let call = super::Call::batch_transfer { transfers: vec![(bob, 6), (bob, 6)] };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::InsufficientBalance));
+		let call = super::Call::batch_transfer_atomic { transfers: vec![(bob, 5), (bob, 5)] };
+		assert_eq!(balances.can_dispatch(&origin, &call), Ok(()));
+
+		// Bob can only transfer what alice has allowed him to.
+		let call = super::Call::transfer_from { owner: alice, to: bob, amount: 5 };
+		assert_eq!(
+			balances.can_dispatch(&Origin::Signed(bob), &call),
+			Err(DispatchError::InsufficientAllowance)
+		);
+		balances.approve(alice, bob, 5).unwrap();
+		assert_eq!(balances.can_dispatch(&Origin::Signed(bob), &call), Ok(()));
+
+		balances.lock(&alice, 5, 10);
+		let call = super::Call::burn { amount: 6 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::FundsLocked));
+		let call = super::Call::transfer_all { to: bob };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::FundsLocked));
+
+		balances.freeze(&alice);
+		let call = super::Call::transfer_saturating { to: bob, amount: 1 };
+		assert_eq!(balances.can_dispatch(&origin, &call), Err(DispatchError::AccountFrozen));
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.balance(&bob), 0);
+	}
+
+	#[test]
+	fn block_reward() {
+		use crate::support::Hooks;
+
+		let mut balances = super::Pallet::<FeeConfig>::new();
+
+		for n in 1..=3 {
+			balances.on_initialize(n);
+		}
+		assert_eq!(balances.balance(&"author"), 30);
+		assert_eq!(balances.total_issuance(), 30);
+
+		// Without an author, there is no reward.
+		let mut balances = super::Pallet::<TestConfig>::new();
+		balances.on_initialize(1);
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn slash() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 100).unwrap();
+		balances.reserve(&alice, 30).unwrap();
+
+		// The free balance is slashed first, then the reserved balance.
+		assert_eq!(balances.slash(&alice, 80), (80, 0));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.reserved_balance(&alice), 20);
+		assert_eq!(balances.total_issuance(), 20);
+
+		// Only what is there can be slashed.
+		assert_eq!(balances.slash(&alice, 50), (20, 30));
+		assert_eq!(balances.reserved_balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 0);
+
+		balances.mint(&bob, 40).unwrap();
+		assert_eq!(balances.slash(&bob, 40), (40, 0));
+		assert_eq!(balances.balance(&bob), 0);
+		assert_eq!(balances.total_issuance(), 0);
+	}
+
+	#[test]
+	fn transfer_keep_alive() {
+		let mut balances = super::Pallet::<MinimumBalanceConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 100).unwrap();
+
+		// Leaving alice with 4 units would reap the account, unlike a plain `transfer`.
+		assert_eq!(
+			balances.transfer_keep_alive(alice, bob, 96),
+			Err(DispatchError::WouldKillAccount)
+		);
+		assert_eq!(
+			balances.transfer_keep_alive(alice, bob, 100),
+			Err(DispatchError::WouldKillAccount)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(
+			balances.transfer_keep_alive(alice, bob, 101),
+			Err(DispatchError::InsufficientBalance)
+		);
+
+		// Alice is left with exactly the minimum balance.
+		assert_eq!(balances.transfer_keep_alive(alice, bob, 95), Ok(()));
+		assert_eq!(balances.balance(&alice), 5);
+		assert_eq!(balances.balance(&bob), 95);
+
+		assert_eq!(balances.transfer(alice, bob, 1), Ok(()));
+		assert!(!balances.balances.contains_key(&alice));
+
+		// The transfer fee counts towards what the sender is left with.
+		let mut balances = super::Pallet::<TransferFeeMinimumConfig>::new();
+		balances.mint(&alice, 200).unwrap();
+		assert_eq!(
+			balances.transfer_keep_alive(alice, bob, 195),
+			Err(DispatchError::WouldKillAccount)
+		);
+		assert_eq!(balances.balance(&alice), 200);
+		assert_eq!(balances.transfer_keep_alive(alice, bob, 194), Ok(()));
+		assert_eq!(balances.balance(&alice), 5);
+	}
+
+	#[test]
+	fn initial_balance() {
+		let mut balances = super::Pallet::<InitialBalanceConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.transfer(alice, bob, 5), Ok(()));
+		assert_eq!(balances.balance(&alice), 5);
+		assert_eq!(balances.balance(&bob), 15);
+
+		// A drained account does not get its initial balance back.
+		assert_eq!(balances.transfer(alice, bob, 5), Ok(()));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.transfer(alice, bob, 1), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.balance(&bob), 20);
+	}
+
+	#[test]
+	fn max_transfer() {
+		let mut balances = super::Pallet::<MaxTransferConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.set_balance(&alice, 100);
+		assert_eq!(balances.transfer(alice, bob, 60), Err(DispatchError::TransferExceedsLimit));
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.balance(&bob), 0);
+
+		// Each transfer of an atomic batch is capped, and the batch is rolled back if one is not.
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 10), ("charlie", 60)]),
+			Err(DispatchError::TransferExceedsLimit)
+		);
+		assert_eq!(balances.balance(&alice), 100);
+		assert_eq!(balances.balance(&bob), 0);
+
+		// A saturating transfer is clamped to the cap.
+		assert_eq!(balances.transfer_saturating(alice, "charlie", 60), Ok(()));
+		assert_eq!(balances.balance(&"charlie"), 50);
+		balances.set_balance(&alice, 100);
+
+		assert_eq!(balances.transfer(alice, bob, 50), Ok(()));
+		assert_eq!(balances.balance(&alice), 50);
+		assert_eq!(balances.balance(&bob), 50);
+	}
+
+	#[test]
+	fn format_balance() {
+		let mut balances = super::Pallet::<TokenConfig>::new();
+		let alice = "alice";
+
+		balances.set_balance(&alice, 12345);
+		assert_eq!(balances.format_balance(&alice), "123.45 SYM");
+		balances.set_balance(&alice, 5);
+		assert_eq!(balances.format_balance(&alice), "0.05 SYM");
+		assert_eq!(balances.format_balance(&"bob"), "0.00 SYM");
+
+		// Without decimals, the balance is displayed as is.
+		let mut balances = super::Pallet::<TestConfig>::new();
+		balances.set_balance(&alice, 12345);
+		assert_eq!(balances.format_balance(&alice), "12345 UNIT");
+	}
+
+	#[test]
+	fn clear_balance() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 100).unwrap();
+		balances.mint(&bob, 50).unwrap();
+
+		assert_eq!(balances.clear_balance(&alice), 100);
+		assert!(!balances.balances.contains_key(&alice));
+		assert_eq!(balances.balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 50);
+
+		// Clearing an account with no stored balance removes nothing.
+		assert_eq!(balances.clear_balance(&alice), 0);
+		assert_eq!(balances.total_issuance(), 50);
+
+		// Setting a balance to zero keeps the entry.
+		balances.set_balance(&bob, 0);
+		assert!(balances.balances.contains_key(&bob));
+	}
+
+	#[test]
+	fn transfer_fee() {
+		let mut balances = super::Pallet::<TransferFeeConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 1_000).unwrap();
+		assert_eq!(balances.transfer(alice, bob, 500), Ok(()));
+		assert_eq!(balances.balance(&alice), 495);
+		assert_eq!(balances.balance(&bob), 500);
+		assert_eq!(balances.balance(&"treasury"), 5);
+		assert_eq!(balances.total_issuance(), 1_000);
+
+		// The fee is rounded down, so small transfers are free.
+		assert_eq!(balances.transfer(alice, bob, 99), Ok(()));
+		assert_eq!(balances.balance(&alice), 396);
+
+		// The sender must be able to pay the fee on top of the amount.
+		assert_eq!(balances.transfer(alice, bob, 396), Err(DispatchError::InsufficientBalance));
+		assert_eq!(balances.transfer(alice, bob, 392), Ok(()));
+		assert_eq!(balances.balance(&alice), 1);
+
+		// The fees are also paid in an atomic batch, so one which cannot be paid rolls it back.
+		let mut balances = super::Pallet::<TransferFeeConfig>::new();
+		balances.mint(&alice, 1_000).unwrap();
+		balances.take_account_changes();
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 500), ("charlie", 495)]),
+			Err(DispatchError::InsufficientBalance)
+		);
+		assert_eq!(balances.balance(&alice), 1_000);
+		assert_eq!(balances.balance(&bob), 0);
+		assert_eq!(balances.balance(&"treasury"), 0);
+		assert_eq!(balances.total_issuance(), 1_000);
+		assert!(balances.events().is_empty());
+		assert!(balances.take_account_changes().is_empty());
+	}
+
+	#[test]
+	fn transfer_with_memo() {
+		let mut balances = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		balances.mint(&alice, 100).unwrap();
+		assert_eq!(
+			balances.transfer_with_memo(alice, bob, 30, vec![0; 33]),
+			Err(DispatchError::MemoTooLong)
+		);
+		assert_eq!(balances.balance(&bob), 0);
+		assert!(balances.events().is_empty());
+
+		assert_eq!(balances.transfer_with_memo(alice, bob, 30, b"invoice 42".to_vec()), Ok(()));
+		assert_eq!(balances.balance(&alice), 70);
+		assert_eq!(balances.balance(&bob), 30);
+		assert_eq!(
+			balances.events().last(),
+			Some(&super::Event::TransferredWithMemo {
+				from: alice,
+				to: bob,
+				amount: 30,
+				memo: b"invoice 42".to_vec(),
+			})
+		);
+	}
+
+	#[test]
+	fn require_account_creation() {
+		let alice = "alice";
+		let bob = "bob";
+
+		// Without the flag, a transfer creates its recipient.
+		let mut balances = super::Pallet::<TestConfig>::new();
+		balances.mint(&alice, 100).unwrap();
+		assert!(!balances.account_exists(&bob));
+		assert_eq!(balances.transfer(alice, bob, 10), Ok(()));
+		assert!(balances.account_exists(&bob));
+
+		// With the flag, the recipient must have been created first.
+		let mut balances = super::Pallet::<AccountCreationConfig>::new();
+		balances.mint(&alice, 100).unwrap();
+		assert_eq!(balances.transfer(alice, bob, 10), Err(DispatchError::AccountNotFound));
+		assert_eq!(balances.balance(&bob), 0);
+		assert_eq!(
+			balances.transfer_saturating(alice, "nobody", 10),
+			Err(DispatchError::AccountNotFound)
+		);
+		assert!(!balances.account_exists(&"nobody"));
+
+		balances.note_created_account(&bob);
+		assert_eq!(balances.transfer(alice, bob, 10), Ok(()));
+		assert_eq!(balances.balance(&bob), 10);
+		// Accounts with a stored balance exist, so they can receive transfers.
+		assert_eq!(balances.transfer(bob, alice, 5), Ok(()));
+
+		// An atomic batch into an account which does not exist is rolled back.
+		assert_eq!(
+			balances.batch_transfer_atomic(alice, vec![(bob, 10), ("nobody", 10)]),
+			Err(DispatchError::AccountNotFound)
+		);
+		assert_eq!(balances.balance(&alice), 95);
+		assert_eq!(balances.balance(&bob), 5);
+	}
+
+	#[test]
+	fn faucet() {
+		let mut balances = super::Pallet::<FaucetConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		assert_eq!(balances.faucet(&alice, 1), Ok(()));
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.total_issuance(), 10);
+
+		// Another drip within the cooldown fails, but other accounts can still get one.
+		assert_eq!(balances.faucet(&alice, 5), Err(DispatchError::FaucetCooldown));
+		assert_eq!(balances.balance(&alice), 10);
+		assert_eq!(balances.faucet(&bob, 5), Ok(()));
+
+		// Once the cooldown is over, the account can get another drip.
+		assert_eq!(balances.faucet(&alice, 6), Ok(()));
+		assert_eq!(balances.balance(&alice), 20);
+		assert_eq!(balances.faucet(&alice, 10), Err(DispatchError::FaucetCooldown));
+	}
 }
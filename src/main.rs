@@ -1,9 +1,24 @@
+use core::hash::{Hash, Hasher};
+use std::collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet};
+
 use crate::{support::Dispatch, types::AccountId};
 
+#[cfg(feature = "assets")]
+mod assets;
 mod balances;
+mod multisig;
+#[cfg(feature = "nft")]
+mod nft;
 mod proof_of_existence;
+mod repl;
+mod scheduler;
 mod support;
 mod system;
+mod timestamp;
+mod treasury;
+mod vesting;
+#[cfg(feature = "voting")]
+mod voting;
 
 // These are the concrete types we will use in our simple state machine.
 // Modules are configured for these types directly, and they satisfy all of our
@@ -11,48 +26,524 @@ mod system;
 mod types {
 	use crate::{support, RuntimeCall};
 
-	pub type AccountId = String;
+	pub type AccountId = support::AccountId;
 	pub type Balance = u128;
 	pub type BlockNumber = u32;
 	pub type Nonce = u32;
-	pub type Extrinsic = support::Extrinsic<AccountId, RuntimeCall>;
+	pub type Hash = [u8; 32];
+	pub type Extrinsic = support::Extrinsic<AccountId, Nonce, RuntimeCall>;
 	pub type Header = support::Header<BlockNumber>;
 	pub type Block = support::Block<Header, Extrinsic>;
-	pub type Content = &'static str;
+	pub type RawContent = String;
+	pub type Content = u64;
+	pub type Metadata = String;
+	pub type Moment = u64;
+	#[cfg(feature = "assets")]
+	pub type AssetId = u32;
+	#[cfg(feature = "voting")]
+	pub type ProposalId = u32;
+	#[cfg(feature = "nft")]
+	pub type ItemId = u32;
 }
 
 // This is our main Runtime.
-// It accumulates all of the different pallets we want to use.
-#[derive(Debug)]
+// It accumulates all of the different pallets we want to use. The pallets which nothing else
+// depends on are each gated by a Cargo feature, so they can be left out of the runtime.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[macros::runtime]
 pub struct Runtime {
 	system: system::Pallet<Self>,
+	#[pallet(currency)]
 	balances: balances::Pallet<Self>,
 	proof_of_existence: proof_of_existence::Pallet<Self>,
+	timestamp: timestamp::Pallet<Self>,
+	#[cfg(feature = "assets")]
+	assets: assets::Pallet<Self>,
+	#[cfg(feature = "voting")]
+	#[pallet(voting)]
+	voting: voting::Pallet<Self>,
+	#[pallet(scheduler)]
+	scheduler: scheduler::Pallet<Self>,
+	#[pallet(multisig)]
+	multisig: multisig::Pallet<Self>,
+	#[pallet(treasury)]
+	treasury: treasury::Pallet<Self>,
+	#[cfg(feature = "nft")]
+	nft: nft::Pallet<Self>,
+	#[pallet(vesting)]
+	vesting: vesting::Pallet<Self>,
+}
+
+// These are all the events which can be emitted by the pallets in our runtime.
+// Note that it is just an accumulation of the events emitted by each pallet.
+#[derive(Debug, Clone)]
+#[allow(non_camel_case_types)]
+pub enum RuntimeEvent {
+	balances(balances::Event<Runtime>),
+	proof_of_existence(proof_of_existence::Event<Runtime>),
+	scheduler(scheduler::Event<Runtime>),
+}
+
+impl From<balances::Event<Runtime>> for RuntimeEvent {
+	fn from(event: balances::Event<Runtime>) -> Self {
+		Self::balances(event)
+	}
+}
+
+impl From<proof_of_existence::Event<Runtime>> for RuntimeEvent {
+	fn from(event: proof_of_existence::Event<Runtime>) -> Self {
+		Self::proof_of_existence(event)
+	}
+}
+
+impl From<scheduler::Event<Runtime>> for RuntimeEvent {
+	fn from(event: scheduler::Event<Runtime>) -> Self {
+		Self::scheduler(event)
+	}
 }
 
 impl system::Config for Runtime {
 	type AccountId = types::AccountId;
 	type BlockNumber = types::BlockNumber;
 	type Nonce = types::Nonce;
+	type RuntimeEvent = RuntimeEvent;
+	type Hash = types::Hash;
+
+	fn block_hash_count() -> Option<Self::BlockNumber> {
+		Some(256)
+	}
+
+	fn max_block_weight() -> support::Weight {
+		support::Weight::new(1_000)
+	}
 }
 
 impl balances::Config for Runtime {
 	type Balance = types::Balance;
+
+	fn weight_to_fee(weight: support::Weight) -> Self::Balance {
+		weight.units().into()
+	}
+
+	fn block_reward() -> Self::Balance {
+		10
+	}
+
+	fn author() -> Option<Self::AccountId> {
+		Some(AccountId::from_name("author"))
+	}
+
+	fn drip_amount() -> Self::Balance {
+		100
+	}
+
+	fn cooldown_blocks() -> Self::BlockNumber {
+		10
+	}
 }
 
 impl proof_of_existence::Config for Runtime {
+	type RawContent = types::RawContent;
 	type Content = types::Content;
+	type Metadata = types::Metadata;
+
+	fn max_claims() -> u32 {
+		10
+	}
+
+	fn hash(content: &Self::RawContent) -> Self::Content {
+		let mut hasher = DefaultHasher::new();
+		content.hash(&mut hasher);
+		hasher.finish()
+	}
+}
+
+impl timestamp::Config for Runtime {
+	type Moment = types::Moment;
+}
+
+#[cfg(feature = "assets")]
+impl assets::Config for Runtime {
+	type AssetId = types::AssetId;
+	type Balance = types::Balance;
+}
+
+#[cfg(feature = "voting")]
+impl voting::Config for Runtime {
+	type ProposalId = types::ProposalId;
+}
+
+impl scheduler::Config for Runtime {
+	type RuntimeCall = RuntimeCall;
+}
+
+impl multisig::Config for Runtime {
+	type RuntimeCall = RuntimeCall;
+
+	fn multi_account_id(signatories: &[Self::AccountId], threshold: u16) -> Self::AccountId {
+		// The account id is made of a prefix, the threshold, and a hash of the signatories.
+		let mut hasher = DefaultHasher::new();
+		signatories.hash(&mut hasher);
+		let mut bytes = [0; 32];
+		bytes[..8].copy_from_slice(b"multisig");
+		bytes[8..10].copy_from_slice(&threshold.to_be_bytes());
+		bytes[10..18].copy_from_slice(&hasher.finish().to_be_bytes());
+		AccountId::new(bytes)
+	}
+
+	fn hash_call(call: &Self::RuntimeCall) -> Self::Hash {
+		// The hash is made of four stable hashes of the call, each seeded with its position.
+		let mut hash = [0; 32];
+		for (position, chunk) in (0u8..).zip(hash.chunks_mut(8)) {
+			let mut hasher = support::StableHasher::default();
+			position.hash(&mut hasher);
+			call.hash(&mut hasher);
+			chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+		}
+		hash
+	}
+}
+
+impl treasury::Config for Runtime {
+	fn spend_origin() -> Self::AccountId {
+		AccountId::from_name("council")
+	}
+}
+
+#[cfg(feature = "nft")]
+impl nft::Config for Runtime {
+	type ItemId = types::ItemId;
+}
+
+impl vesting::Config for Runtime {
+	fn block_number_to_balance(n: Self::BlockNumber) -> Self::Balance {
+		n.into()
+	}
+}
+
+// The initial state of the runtime, which is set up before any block is produced.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(default))]
+pub struct GenesisConfig {
+	/// The initial balance of each account. These are minted, so they make up the total issuance.
+	pub balances: Vec<(types::AccountId, types::Balance)>,
+	/// The initial claims, as `(owner, raw content, metadata)`.
+	pub claims: Vec<(types::AccountId, types::RawContent, types::Metadata)>,
+	/// The block number the chain starts at, so the first block produced is the one after it.
+	pub initial_block: types::BlockNumber,
+	/// The initial nonce of each account, for example of the accounts imported from another chain.
+	pub nonces: Vec<(types::AccountId, types::Nonce)>,
+}
+
+impl Runtime {
+	/// Create a new instance of the runtime, with all pallets seeded from the genesis `config`.
+	///
+	/// Panics if the genesis config is invalid, since the chain cannot start from it.
+	pub fn new_with_genesis(config: GenesisConfig) -> Self {
+		let mut runtime = Self::new();
+		runtime.system.set_block_number(config.initial_block);
+		let genesis_block = runtime.system.block_number();
+
+		for (who, nonce) in config.nonces {
+			runtime.system.set_nonce(&who, nonce);
+		}
+		for (who, amount) in config.balances {
+			runtime.balances.mint(&who, amount).expect("invalid genesis balance");
+		}
+		// The genesis claims are recorded at the genesis block.
+		support::Hooks::on_initialize(&mut runtime.proof_of_existence, genesis_block);
+		for (owner, content, metadata) in config.claims {
+			runtime
+				.proof_of_existence
+				.create_claim(owner, content, metadata)
+				.expect("invalid genesis claim");
+		}
+
+		runtime
+	}
+
+	/// Create the account `who` explicitly, in the system pallet, and note it in the balances
+	/// pallet, so it can receive transfers.
+	/// This function will return an error if the account already exists.
+	pub fn create_account(&mut self, who: &types::AccountId) -> support::DispatchResult {
+		self.system.create_account(who)?;
+		self.balances.note_created_account(who);
+		Ok(())
+	}
+
+	/// Pause the `call` function of the `pallet`, for example `("balances", "transfer")`, so it is
+	/// filtered out when dispatched, until it is resumed.
+	pub fn pause_call(&mut self, pallet: &str, call: &str) {
+		self.system.pause_call(pallet, call);
+	}
+
+	/// Resume the `call` function of the `pallet`, after it was paused.
+	pub fn resume_call(&mut self, pallet: &str, call: &str) {
+		self.system.resume_call(pallet, call);
+	}
+
+	/// Check whether the `extrinsic` would be applied successfully in the next block, without
+	/// changing any state: it must pass the signed extensions which `apply_extrinsic` runs, like
+	/// the checks of its nonce and fee, and its call must pass the `#[validate(..)]` check of its
+	/// function once the fee is paid. Calls of functions without such a check, like most calls
+	/// outside of the balances pallet, are assumed to succeed, so this does not predict their
+	/// dispatch.
+	pub fn validate(&self, extrinsic: &types::Extrinsic) -> support::DispatchResult {
+		let support::Extrinsic { caller, nonce, call } = extrinsic;
+		support::SignedExtension::validate(&Self::signed_extensions(*nonce), self, caller, call)?;
+
+		// The call is checked against a copy of the state in which the fee is already paid, like it
+		// is when the extrinsic is applied.
+		let mut runtime = self.clone();
+		let fee = call.fee();
+		runtime.balances.deduct_fee(caller, fee)?;
+		runtime.treasury.deposit(fee)?;
+		runtime.can_dispatch(&support::Origin::Signed(caller.clone()), call)
+	}
+
+	/// Dispatch the `call` on behalf of the `caller` as a transaction: if it fails, the state of
+	/// every pallet is restored to how it was before, so a call which fails halfway through does
+	/// not leave any of its changes behind.
+	pub fn dispatch_transactional(
+		&mut self,
+		caller: types::AccountId,
+		call: RuntimeCall,
+	) -> support::DispatchResult {
+		support::with_transaction(self, |runtime| {
+			runtime.dispatch(support::Origin::Signed(caller), call)
+		})
+	}
+
+	/// Check that the state of the runtime is consistent, returning a description of the first
+	/// inconsistency found. This is a debug aid, which can be run after each block.
+	///
+	/// The free and reserved balances of all accounts must add up to the total issuance, and the
+	/// reserved balances alone must not exceed it.
+	///
+	/// The owners of claims are not required to have a nonce: the owners of the genesis claims, and
+	/// multisig accounts which made a claim through an approved call, never signed an extrinsic.
+	pub fn check_invariants(&self) -> Result<(), String> {
+		fn sum<'a>(
+			mut balances: impl Iterator<Item = (&'a types::AccountId, &'a types::Balance)>,
+		) -> Result<types::Balance, String> {
+			balances
+				.try_fold(0, |sum: types::Balance, (_, amount)| sum.checked_add(*amount))
+				.ok_or_else(|| String::from("sum of balances overflows"))
+		}
+		let free = sum(self.balances.accounts())?;
+		let reserved = sum(self.balances.reserved_accounts())?;
+		let total_issuance = self.balances.total_issuance();
+
+		if reserved > total_issuance {
+			return Err(format!(
+				"reserved balances ({}) exceed the total issuance ({})",
+				reserved, total_issuance
+			));
+		}
+		if free.checked_add(reserved) != Some(total_issuance) {
+			return Err(format!(
+				"free ({}) and reserved ({}) balances do not add up to the total issuance ({})",
+				free, reserved, total_issuance
+			));
+		}
+		Ok(())
+	}
+
+	/// Answer the query `q` about the current state, without changing it.
+	pub fn query(&self, q: RuntimeQuery) -> QueryResult {
+		match q {
+			RuntimeQuery::Balance(who) => QueryResult::Balance(self.balances.balance(&who)),
+			RuntimeQuery::Nonce(who) => QueryResult::Nonce(self.system.nonce(&who)),
+			RuntimeQuery::BlockNumber => QueryResult::BlockNumber(self.system.block_number()),
+			RuntimeQuery::ClaimOwner(claim) =>
+				QueryResult::ClaimOwner(self.proof_of_existence.get_claim(&claim).cloned()),
+			RuntimeQuery::TreasuryPot => QueryResult::TreasuryPot(self.treasury.pot()),
+			RuntimeQuery::VestingSchedule(who) =>
+				QueryResult::VestingSchedule(self.vesting.schedule(&who).copied()),
+			#[cfg(feature = "voting")]
+			RuntimeQuery::ProposalResult(proposal_id) =>
+				QueryResult::ProposalResult(self.voting.result(&proposal_id)),
+		}
+	}
+
+	/// Take a snapshot of the health of the runtime, read from each of the pallets.
+	pub fn metrics(&self) -> RuntimeMetrics {
+		RuntimeMetrics {
+			block_number: self.system.block_number(),
+			total_issuance: self.balances.total_issuance(),
+			account_count: self.system.account_count(),
+			total_claims: self.proof_of_existence.total_claims(),
+			events_this_block: self.system.event_records().len(),
+		}
+	}
+
+	/// Get the differences between the state of this runtime and the state of `other`, which is
+	/// taken to be the newer one, for example after executing a block.
+	pub fn diff(&self, other: &Runtime) -> StateDiff {
+		let accounts = self
+			.balances
+			.accounts()
+			.chain(other.balances.accounts())
+			.map(|(who, _)| who)
+			.collect::<BTreeSet<_>>();
+		let balances = accounts
+			.into_iter()
+			.map(|who| (who, self.balances.balance(who), other.balances.balance(who)))
+			.filter(|(_, old, new)| old != new)
+			.map(|(who, old, new)| (who.clone(), (old, new)))
+			.collect();
+
+		let old_nonces = self.system.all_nonces().into_iter().collect::<BTreeMap<_, _>>();
+		let new_nonces = other.system.all_nonces().into_iter().collect::<BTreeMap<_, _>>();
+		let nonces = old_nonces
+			.keys()
+			.chain(new_nonces.keys())
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.map(|who| (who, self.system.nonce(who), other.system.nonce(who)))
+			.filter(|(_, old, new)| old != new)
+			.map(|(who, old, new)| (who.clone(), (old, new)))
+			.collect();
+
+		let claims_of = |runtime: &Runtime| {
+			runtime
+				.proof_of_existence
+				.iter_claims()
+				.map(|(claim, _)| *claim)
+				.collect::<BTreeSet<_>>()
+		};
+		let (old_claims, new_claims) = (claims_of(self), claims_of(other));
+
+		StateDiff {
+			balances,
+			nonces,
+			added_claims: new_claims.difference(&old_claims).copied().collect(),
+			removed_claims: old_claims.difference(&new_claims).copied().collect(),
+		}
+	}
+}
+
+// The differences between the state of two runtimes, as given by `Runtime::diff`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateDiff {
+	/// The accounts whose balance changed, with their `(old, new)` balance.
+	pub balances: BTreeMap<types::AccountId, (types::Balance, types::Balance)>,
+	/// The accounts whose nonce changed, with their `(old, new)` nonce.
+	pub nonces: BTreeMap<types::AccountId, (types::Nonce, types::Nonce)>,
+	/// The content of the claims which only exist in the newer state.
+	pub added_claims: Vec<types::Content>,
+	/// The content of the claims which only exist in the older state.
+	pub removed_claims: Vec<types::Content>,
+}
+
+// A snapshot of the health of the runtime, as given by `Runtime::metrics`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeMetrics {
+	/// The number of the current block.
+	pub block_number: types::BlockNumber,
+	/// The total amount of funds which exist in the `balances` pallet.
+	pub total_issuance: types::Balance,
+	/// The number of accounts which exist.
+	pub account_count: u32,
+	/// The number of claims which exist.
+	pub total_claims: usize,
+	/// The number of events deposited during the current block.
+	pub events_this_block: usize,
+}
+
+// The questions which can be asked about the state of the runtime with `Runtime::query`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeQuery {
+	/// The balance of an account.
+	Balance(types::AccountId),
+	/// The nonce of an account.
+	Nonce(types::AccountId),
+	/// The current block number.
+	BlockNumber,
+	/// The owner of a claim.
+	ClaimOwner(types::Content),
+	/// The funds held by the treasury.
+	TreasuryPot,
+	/// The vesting schedule of an account.
+	VestingSchedule(types::AccountId),
+	/// Whether a proposal is approved by the weight of its votes.
+	#[cfg(feature = "voting")]
+	ProposalResult(types::ProposalId),
+}
+
+// The answers to each of the `RuntimeQuery` variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryResult {
+	/// The balance of the account.
+	Balance(types::Balance),
+	/// The nonce of the account.
+	Nonce(types::Nonce),
+	/// The current block number.
+	BlockNumber(types::BlockNumber),
+	/// The owner of the claim, if it exists.
+	ClaimOwner(Option<types::AccountId>),
+	/// The funds held by the treasury.
+	TreasuryPot(types::Balance),
+	/// The vesting schedule of the account, if it has one.
+	VestingSchedule(Option<vesting::VestingSchedule<types::Balance, types::BlockNumber>>),
+	/// Whether the proposal is approved, if it has any votes.
+	#[cfg(feature = "voting")]
+	ProposalResult(Option<bool>),
+}
+
+#[cfg(feature = "serde")]
+impl Runtime {
+	/// Export the state of every pallet in the runtime as JSON.
+	/// The events of the current block are not part of the exported state.
+	pub fn export_state(&self) -> String {
+		serde_json::to_string(self).expect("runtime state can always be serialized")
+	}
+
+	/// Create a runtime from state previously exported with `export_state`.
+	pub fn import_state(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+
+	/// Create a runtime from a genesis config given as JSON, with the `balances`, `claims` and
+	/// `initial_block` fields of `GenesisConfig`. Missing fields are left empty.
+	///
+	/// Panics if the genesis config is invalid, like `new_with_genesis`.
+	pub fn from_genesis_json(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json).map(Self::new_with_genesis)
+	}
+
+	/// Save the state of the runtime to a JSON file at `path`.
+	pub fn save_to_file(&self, path: &std::path::Path) -> std::io::Result<()> {
+		std::fs::write(path, self.export_state())
+	}
+
+	/// Load the state of the runtime from a JSON file at `path`.
+	/// If there is no file at `path`, we return a fresh runtime.
+	pub fn load_from_file(path: &std::path::Path) -> std::io::Result<Self> {
+		match std::fs::read_to_string(path) {
+			Ok(json) => Ok(Self::import_state(&json)?),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+			Err(e) => Err(e),
+		}
+	}
 }
 
 fn main() {
-	let mut runtime = Runtime::new();
+	let alice = AccountId::from_name("alice");
+	let bob = AccountId::from_name("bob");
+	let charlie = AccountId::from_name("charlie");
 
-	let alice = AccountId::from("alice");
-	let bob = AccountId::from("bob");
-	let charlie = AccountId::from("charlie");
+	let mut runtime = Runtime::new_with_genesis(GenesisConfig {
+		balances: vec![(alice.clone(), 100)],
+		..Default::default()
+	});
 
-	runtime.balances.set_balance(&alice, 100);
+	// With the `repl` argument, the commands typed into stdin are run instead of the scenario.
+	if std::env::args().nth(1).as_deref() == Some("repl") {
+		repl::run(&mut runtime, std::io::stdin().lock(), std::io::stdout()).expect("io error");
+		return;
+	}
 
 	// start emulating a block
 	let first_block = types::Block {
@@ -61,6 +552,7 @@ fn main() {
 			// first transaction
 			support::Extrinsic {
 				caller: alice.clone(),
+				nonce: 1,
 				call: RuntimeCall::balances(balances::Call::transfer {
 					to: bob.clone(),
 					amount: 30,
@@ -69,6 +561,7 @@ fn main() {
 			// second transaction
 			support::Extrinsic {
 				caller: alice.clone(),
+				nonce: 2,
 				call: RuntimeCall::balances(balances::Call::transfer { to: charlie, amount: 20 }),
 			},
 		],
@@ -79,14 +572,18 @@ fn main() {
 		extrinsics: vec![
 			support::Extrinsic {
 				caller: alice.clone(),
+				nonce: 3,
 				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: "Some text",
+					claim: String::from("Some text"),
+					metadata: String::new(),
 				}),
 			},
 			support::Extrinsic {
 				caller: bob.clone(),
+				nonce: 1,
 				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-					claim: "Some text longer",
+					claim: String::from("Some text longer"),
+					metadata: String::new(),
 				}),
 			},
 		],
@@ -97,3 +594,1917 @@ fn main() {
 
 	println!("runtime state: {:#?}", runtime);
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		balances, multisig, proof_of_existence, scheduler, support, support::Dispatch, system,
+		timestamp, treasury, types, types::AccountId, vesting, GenesisConfig, QueryResult, Runtime,
+		RuntimeCall, RuntimeEvent, RuntimeMetrics, RuntimeQuery, StateDiff,
+	};
+
+	// Get the content which is stored for the claim of the `raw` content.
+	fn content(raw: &str) -> types::Content {
+		<Runtime as proof_of_existence::Config>::hash(&String::from(raw))
+	}
+
+	#[test]
+	fn genesis_config() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		let runtime = Runtime::new_with_genesis(GenesisConfig {
+			balances: vec![(alice.clone(), 100), (bob.clone(), 50)],
+			claims: vec![(bob.clone(), String::from("document"), String::from("genesis"))],
+			..Default::default()
+		});
+
+		assert_eq!(runtime.system.block_number(), 0);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&bob), 50);
+		assert_eq!(runtime.balances.total_issuance(), 150);
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&bob));
+		assert_eq!(runtime.proof_of_existence.claim_block(&content("document")), Some(0));
+		assert_eq!(
+			runtime.proof_of_existence.claim_metadata(&content("document")),
+			Some(&String::from("genesis"))
+		);
+	}
+
+	#[test]
+	fn genesis_nonces() {
+		let alice = AccountId::from_name("alice");
+
+		let mut runtime = Runtime::new_with_genesis(GenesisConfig {
+			balances: vec![(alice.clone(), 100)],
+			nonces: vec![(alice.clone(), 7)],
+			..Default::default()
+		});
+		assert_eq!(runtime.system.nonce(&alice), 7);
+		assert!(runtime.system.account_exists(&alice));
+
+		// The first transaction of alice carries the nonce after the seeded one.
+		let transfer = |nonce| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::balances(balances::Call::transfer {
+				to: AccountId::from_name("bob"),
+				amount: 10,
+			}),
+		};
+		assert_eq!(runtime.apply_extrinsic(transfer(1)), Err(support::DispatchError::InvalidNonce));
+		assert_eq!(runtime.apply_extrinsic(transfer(8)), Ok(()));
+		assert_eq!(runtime.system.nonce(&alice), 8);
+	}
+
+	#[test]
+	fn dispatch_runtime_calls() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let call = RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 40 });
+		assert!(runtime.dispatch(support::Origin::Signed(alice.clone()), call).is_ok());
+		assert_eq!(runtime.balances.balance(&alice), 60);
+		assert_eq!(runtime.balances.balance(&bob), 40);
+
+		let call = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: String::from("document"),
+			metadata: String::new(),
+		});
+		assert!(runtime.dispatch(support::Origin::Signed(bob.clone()), call).is_ok());
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&bob));
+	}
+
+	#[test]
+	fn execute_block_with_transfers() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 30,
+					}),
+				},
+				// This extrinsic fails, since charlie cannot pay its fee, but does not stop the
+				// rest of the block.
+				support::Extrinsic {
+					caller: charlie.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 10,
+					}),
+				},
+				support::Extrinsic {
+					caller: bob.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: charlie.clone(),
+						amount: 10,
+					}),
+				},
+			],
+		};
+		assert!(runtime.execute_block(block).is_ok());
+
+		assert_eq!(runtime.system.block_number(), 1);
+		assert_eq!(runtime.system.nonce(&alice), 1);
+		assert_eq!(runtime.system.nonce(&bob), 1);
+		assert_eq!(runtime.system.nonce(&charlie), 0);
+		// Each applied transfer costs its caller a fee of 1.
+		assert_eq!(runtime.balances.balance(&alice), 69);
+		assert_eq!(runtime.balances.balance(&bob), 19);
+		assert_eq!(runtime.balances.balance(&charlie), 10);
+	}
+
+	#[test]
+	fn execute_block_with_wrong_number() {
+		let mut runtime = Runtime::new();
+
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		assert_eq!(runtime.execute_block(block), Err(support::DispatchError::InvalidBlockNumber));
+		assert_eq!(runtime.system.block_number(), 0);
+	}
+
+	#[test]
+	fn execute_out_of_order_block() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		let block =
+			types::Block { header: support::Header { block_number: 1 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		let author_balance = runtime.balances.balance(&AccountId::from_name("author"));
+
+		let block = |block_number| types::Block {
+			header: support::Header { block_number },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: RuntimeCall::balances(balances::Call::transfer {
+					to: bob.clone(),
+					amount: 10,
+				}),
+			}],
+		};
+		// Neither skipping ahead nor replaying a block changes any state.
+		for block_number in [3, 1] {
+			assert_eq!(
+				runtime.execute_block(block(block_number)),
+				Err(support::DispatchError::InvalidBlockNumber)
+			);
+			assert_eq!(runtime.system.block_number(), 1);
+			assert_eq!(runtime.system.nonce(&alice), 0);
+			assert_eq!(runtime.balances.balance(&alice), 100);
+			assert_eq!(runtime.balances.balance(&bob), 0);
+			assert_eq!(runtime.balances.balance(&AccountId::from_name("author")), author_balance);
+		}
+
+		assert_eq!(runtime.execute_block(block(2)), Ok(()));
+		assert_eq!(runtime.system.block_number(), 2);
+	}
+
+	#[test]
+	fn execute_empty_block() {
+		let mut runtime = Runtime::new();
+		let empty_block = |block_number| types::Block {
+			header: support::Header { block_number },
+			extrinsics: vec![],
+		};
+
+		assert!(!runtime.system.reject_empty_blocks());
+		assert_eq!(runtime.execute_block(empty_block(1)), Ok(()));
+
+		runtime.system.set_reject_empty_blocks(true);
+		assert_eq!(runtime.execute_block(empty_block(2)), Err(support::DispatchError::EmptyBlock));
+		assert_eq!(runtime.system.block_number(), 1);
+	}
+
+	#[test]
+	fn execute_block_with_wrong_nonce() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				// An extrinsic skipping ahead of the nonce alice is expected to use next.
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 2,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 30,
+					}),
+				},
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 20,
+					}),
+				},
+				// Replaying the extrinsic which was just applied is rejected.
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 20,
+					}),
+				},
+			],
+		};
+		assert!(runtime.execute_block(block).is_ok());
+
+		assert_eq!(runtime.system.nonce(&alice), 1);
+		assert_eq!(runtime.balances.balance(&alice), 79);
+		assert_eq!(runtime.balances.balance(&bob), 20);
+	}
+
+	#[test]
+	fn execute_block_collecting_errors() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 60,
+					}),
+				},
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 2,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 60,
+					}),
+				},
+				support::Extrinsic {
+					caller: bob.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: alice.clone(),
+						amount: 10,
+					}),
+				},
+			],
+		};
+		let errors = runtime.execute_block_collect_errors(block).unwrap();
+
+		assert_eq!(errors, vec![(1, support::DispatchError::InsufficientBalance)]);
+		// The fee of the failed transfer is still paid.
+		assert_eq!(runtime.balances.balance(&alice), 48);
+		assert_eq!(runtime.balances.balance(&bob), 49);
+
+		let block =
+			types::Block { header: support::Header { block_number: 1 }, extrinsics: vec![] };
+		assert_eq!(
+			runtime.execute_block_collect_errors(block),
+			Err(support::DispatchError::InvalidBlockNumber)
+		);
+	}
+
+	#[test]
+	fn block_events() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 30,
+					}),
+				},
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 2,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 20,
+					}),
+				},
+			],
+		};
+		runtime.execute_block(block).unwrap();
+
+		let events = runtime.system.events();
+		assert_eq!(events.len(), 2);
+		assert!(matches!(
+			&events[1],
+			RuntimeEvent::balances(balances::Event::Transferred { amount: 20, .. })
+		));
+
+		// The events are cleared when the next block starts.
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		assert!(runtime.system.events().is_empty());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn export_and_import_state() {
+		use crate::support::Dispatch;
+
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		let call = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: String::from("document"),
+			metadata: String::new(),
+		});
+		runtime.dispatch(support::Origin::Signed(bob.clone()), call).unwrap();
+		runtime.system.inc_nonce(&bob).unwrap();
+		runtime.system.inc_block_number().unwrap();
+
+		let state = runtime.export_state();
+
+		// Mutate the runtime after the export.
+		let call = RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 40 });
+		runtime.dispatch(support::Origin::Signed(alice.clone()), call).unwrap();
+		assert_ne!(runtime.export_state(), state);
+
+		let imported = Runtime::import_state(&state).unwrap();
+		assert_eq!(imported.export_state(), state);
+		assert_eq!(imported.system.block_number(), 1);
+		assert_eq!(imported.system.nonce(&bob), 1);
+		assert_eq!(imported.balances.balance(&alice), 100);
+		assert_eq!(imported.balances.balance(&bob), 0);
+		assert_eq!(imported.balances.total_issuance(), 100);
+		assert_eq!(imported.proof_of_existence.get_claim(&content("document")), Some(&bob));
+
+		// A state without storage versions is imported at version 0, and can then be upgraded.
+		let mut value: serde_json::Value = serde_json::from_str(&state).unwrap();
+		for pallet in value.as_object_mut().unwrap().values_mut() {
+			if let Some(pallet) = pallet.as_object_mut() {
+				pallet.remove("storage_version");
+			}
+		}
+		let mut imported = Runtime::import_state(&value.to_string()).unwrap();
+		assert_eq!(support::Migrate::storage_version(&imported.proof_of_existence), 0);
+		imported.upgrade();
+		assert_eq!(support::Migrate::storage_version(&imported.proof_of_existence), 1);
+
+		assert!(Runtime::import_state("not json").is_err());
+	}
+
+	#[test]
+	fn pallet_index() {
+		let transfer = RuntimeCall::balances(balances::Call::transfer {
+			to: AccountId::from_name("bob"),
+			amount: 1,
+		});
+		let claim = RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
+			claim: content("document"),
+		});
+		assert_eq!(Runtime::pallet_index(&transfer), 1);
+		assert_eq!(Runtime::pallet_index(&claim), 2);
+	}
+
+	#[test]
+	fn execution_order() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let extrinsic = |caller: &AccountId, nonce, call| support::Extrinsic {
+			caller: caller.clone(),
+			nonce,
+			call,
+		};
+		let claim = |raw: &str| {
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: String::from(raw),
+				metadata: String::new(),
+			})
+		};
+		let block = || types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				extrinsic(&alice, 1, claim("document")),
+				extrinsic(
+					&alice,
+					2,
+					RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 50 }),
+				),
+				// Bob can only pay for the claim with the funds transferred to them just before.
+				extrinsic(&bob, 1, claim("record")),
+			],
+		};
+		let genesis = || {
+			Runtime::new_with_genesis(GenesisConfig {
+				balances: vec![(alice.clone(), 100)],
+				..Default::default()
+			})
+		};
+
+		// The extrinsics are applied in the order of the block, not grouped by pallet.
+		let mut runtime = genesis();
+		assert_eq!(runtime.execute_block_collect_errors(block()), Ok(vec![]));
+		let phases = runtime
+			.system
+			.event_records()
+			.into_iter()
+			.map(|record| match &record.event {
+				RuntimeEvent::balances(_) => ("balances", record.phase),
+				RuntimeEvent::proof_of_existence(_) => ("proof_of_existence", record.phase),
+				RuntimeEvent::scheduler(_) => ("scheduler", record.phase),
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			phases,
+			vec![
+				("proof_of_existence", system::Phase::ApplyExtrinsic(0)),
+				("balances", system::Phase::ApplyExtrinsic(1)),
+				("proof_of_existence", system::Phase::ApplyExtrinsic(2)),
+			]
+		);
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&alice));
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("record")), Some(&bob));
+
+		// Executing the same block on the same state leads to the same state.
+		let mut other = genesis();
+		other.execute_block(block()).unwrap();
+		assert_eq!(runtime.diff(&other), StateDiff::default());
+	}
+
+	#[test]
+	fn killed_account_claims() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let mut runtime = Runtime::new_with_genesis(GenesisConfig {
+			balances: vec![(alice.clone(), 100), (bob.clone(), 100)],
+			claims: vec![
+				(alice.clone(), String::from("document"), String::new()),
+				(bob.clone(), String::from("record"), String::new()),
+			],
+			..Default::default()
+		});
+
+		// All the funds of alice are taken away, and alice has never made a transaction, so their
+		// account has no providers left: it is reaped, along with their claims.
+		runtime.balances.force_transfer(alice.clone(), bob.clone(), 100).unwrap();
+		let block =
+			types::Block { header: support::Header { block_number: 1 }, extrinsics: vec![] };
+		assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.system.providers(&alice), 0);
+		assert!(runtime.proof_of_existence.get_claim(&content("document")).is_none());
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("record")), Some(&bob));
+	}
+
+	#[test]
+	fn fee_drains_claimer() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		runtime.balances.mint(&alice, 1).unwrap();
+
+		// The fee of the claim takes the whole balance of alice, but the nonce of the extrinsic
+		// keeps their account alive, so the claim is kept.
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+					claim: String::from("document"),
+					metadata: String::new(),
+				}),
+			}],
+		};
+		assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.system.providers(&alice), 1);
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&alice));
+	}
+
+	#[test]
+	fn claim_events() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let document = content("document");
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let create = RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+			claim: String::from("document"),
+			metadata: String::new(),
+		});
+		let revoke = RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
+			claim: document,
+		});
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic { caller: alice.clone(), nonce: 1, call: create },
+				support::Extrinsic { caller: alice.clone(), nonce: 2, call: revoke },
+			],
+		};
+		runtime.execute_block(block).unwrap();
+
+		let events = runtime.system.events();
+		assert_eq!(events.len(), 2);
+		assert!(matches!(
+			events[0],
+			RuntimeEvent::proof_of_existence(proof_of_existence::Event::ClaimCreated {
+				owner,
+				content,
+			}) if *owner == alice && *content == document
+		));
+		assert!(matches!(
+			events[1],
+			RuntimeEvent::proof_of_existence(proof_of_existence::Event::ClaimRevoked {
+				owner,
+				content,
+			}) if *owner == alice && *content == document
+		));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn genesis_from_json() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		let json = format!(
+			r#"{{
+				"balances": [["{alice}", 100], ["{bob}", 50]],
+				"claims": [["{bob}", "document", "genesis"]],
+				"initial_block": 10
+			}}"#
+		);
+		let mut runtime = Runtime::from_genesis_json(&json).unwrap();
+
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&bob), 50);
+		assert_eq!(runtime.balances.total_issuance(), 150);
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&bob));
+		assert_eq!(runtime.proof_of_existence.claim_block(&content("document")), Some(10));
+		assert_eq!(runtime.system.block_number(), 10);
+
+		// The next block is the one after the initial block.
+		let block =
+			types::Block { header: support::Header { block_number: 11 }, extrinsics: vec![] };
+		assert_eq!(runtime.execute_block(block), Ok(()));
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn genesis_from_malformed_json() {
+		assert!(Runtime::from_genesis_json("not json").is_err());
+		let alice = AccountId::from_name("alice");
+		let json = format!(r#"{{ "balances": [["{alice}", -1]] }}"#);
+		assert!(Runtime::from_genesis_json(&json).is_err());
+		// Accounts are given by their address, not their name.
+		let error = Runtime::from_genesis_json(r#"{ "balances": [["alice", 1]] }"#).unwrap_err();
+		assert!(error.to_string().contains("address must start with 0x"));
+		assert!(Runtime::from_genesis_json(r#"{ "initial_block": "ten" }"#).is_err());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn save_and_load_state_file() {
+		let path = std::env::temp_dir()
+			.join(format!("rust-state-machine-{}-state.json", std::process::id()));
+		let alice = AccountId::from_name("alice");
+
+		// There is no saved state yet, so we start from a fresh runtime.
+		let mut runtime = Runtime::load_from_file(&path).unwrap();
+		assert_eq!(runtime.balances.balance(&alice), 0);
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.save_to_file(&path).unwrap();
+
+		let loaded = Runtime::load_from_file(&path).unwrap();
+		assert_eq!(loaded.balances.balance(&alice), 100);
+
+		std::fs::write(&path, "not json").unwrap();
+		let err = Runtime::load_from_file(&path).unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+		std::fs::remove_file(&path).unwrap();
+	}
+
+	#[test]
+	fn block_extrinsic_count() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 30,
+					}),
+				},
+				support::Extrinsic {
+					caller: bob.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: alice.clone(),
+						amount: 10,
+					}),
+				},
+			],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.system.extrinsic_count(), 2);
+
+		// The count is reset when the next block starts.
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.system.extrinsic_count(), 0);
+	}
+
+	#[test]
+	fn execute_block_within_weight_limit() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		// Each transfer has a weight of 1, so only two of them fit in a block.
+		runtime.system.set_max_block_weight(support::Weight::new(2));
+
+		let transfer = |nonce, amount| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount }),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![transfer(1, 10), transfer(2, 20), transfer(3, 30)],
+		};
+		let errors = runtime.execute_block_collect_errors(block).unwrap();
+
+		assert_eq!(errors, vec![(2, support::DispatchError::ExhaustsResources)]);
+		assert_eq!(runtime.system.block_weight(), support::Weight::new(2));
+		assert_eq!(runtime.system.nonce(&alice), 2);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+
+		let batch = RuntimeCall::balances(balances::Call::batch_transfer { transfers: vec![] });
+		assert_eq!(batch.weight(), support::Weight::new(10));
+	}
+
+	#[test]
+	fn execute_scheduled_transfer() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let transfer =
+			RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 30 });
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: RuntimeCall::scheduler(scheduler::Call::schedule {
+					when: 3,
+					call: Box::new(transfer),
+				}),
+			}],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.balances.balance(&bob), 0);
+
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.balances.balance(&bob), 0);
+
+		// The transfer is dispatched on behalf of alice once block 3 is reached.
+		let block =
+			types::Block { header: support::Header { block_number: 3 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		// Alice paid the fee of scheduling the transfer, and then the fee of the transfer itself.
+		assert_eq!(runtime.balances.balance(&alice), 68);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert!(runtime.scheduler.agenda(&3).is_empty());
+		assert!(matches!(
+			runtime.system.events().as_slice(),
+			[
+				RuntimeEvent::balances(balances::Event::Transferred { amount: 30, .. }),
+				RuntimeEvent::scheduler(scheduler::Event::Dispatched {
+					when: 3,
+					result: Ok(()),
+					..
+				}),
+			]
+		));
+	}
+
+	#[test]
+	fn scheduled_calls_within_weight_limit() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let schedule = |nonce, amount| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::scheduler(scheduler::Call::schedule {
+				when: 2,
+				call: Box::new(RuntimeCall::balances(balances::Call::transfer {
+					to: bob.clone(),
+					amount,
+				})),
+			}),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![schedule(1, 10), schedule(2, 20), schedule(3, 30)],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.balances.balance(&alice), 97);
+
+		// Each transfer has a weight of 1, so only two of the scheduled transfers fit in block 2.
+		runtime.system.set_max_block_weight(support::Weight::new(2));
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+
+		assert_eq!(runtime.system.block_weight(), support::Weight::new(2));
+		assert_eq!(runtime.balances.balance(&alice), 65);
+		assert_eq!(runtime.balances.balance(&bob), 30);
+		assert!(matches!(
+			runtime.system.events().last(),
+			Some(RuntimeEvent::scheduler(scheduler::Event::Dispatched {
+				when: 2,
+				index: 2,
+				result: Err(support::DispatchError::ExhaustsResources),
+			}))
+		));
+	}
+
+	#[test]
+	fn finalize_hooks() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.lock(&alice, 60, 3);
+
+		for block_number in 1..=3 {
+			assert_eq!(runtime.balances.lock_of(&alice), Some((60, 3)));
+			let block =
+				types::Block { header: support::Header { block_number }, extrinsics: vec![] };
+			runtime.execute_block(block).unwrap();
+		}
+
+		// The lock expired in block 3, so it was removed when that block was finalized.
+		assert_eq!(runtime.balances.lock_of(&alice), None);
+	}
+
+	#[test]
+	fn multisig_transfer() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+		let multi_account = <Runtime as multisig::Config>::multi_account_id(
+			&[alice.clone(), bob.clone(), charlie.clone()],
+			2,
+		);
+
+		runtime.balances.mint(&multi_account, 100).unwrap();
+
+		let approve = |others: [&AccountId; 2]| {
+			let transfer =
+				RuntimeCall::balances(balances::Call::transfer { to: charlie.clone(), amount: 40 });
+			RuntimeCall::multisig(multisig::Call::approve_as_multi {
+				threshold: 2,
+				other_signatories: others.into_iter().cloned().collect(),
+				call: Box::new(transfer),
+			})
+		};
+
+		runtime
+			.dispatch(support::Origin::Signed(alice.clone()), approve([&bob, &charlie]))
+			.unwrap();
+		runtime
+			.dispatch(support::Origin::Signed(alice.clone()), approve([&bob, &charlie]))
+			.unwrap();
+		assert_eq!(runtime.balances.balance(&charlie), 0);
+
+		// The transfer is dispatched from the multisig account on the second distinct approval.
+		runtime
+			.dispatch(support::Origin::Signed(bob.clone()), approve([&alice, &charlie]))
+			.unwrap();
+		assert_eq!(runtime.balances.balance(&multi_account), 60);
+		assert_eq!(runtime.balances.balance(&charlie), 40);
+	}
+
+	#[test]
+	fn multisig_failed_call_keeps_approvals() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+		let multi_account =
+			<Runtime as multisig::Config>::multi_account_id(&[alice.clone(), bob.clone()], 2);
+
+		runtime.balances.mint(&multi_account, 10).unwrap();
+
+		let approve = |other: &AccountId| {
+			let transfer =
+				RuntimeCall::balances(balances::Call::transfer { to: charlie.clone(), amount: 40 });
+			RuntimeCall::multisig(multisig::Call::approve_as_multi {
+				threshold: 2,
+				other_signatories: vec![other.clone()],
+				call: Box::new(transfer),
+			})
+		};
+
+		runtime.dispatch(support::Origin::Signed(alice.clone()), approve(&bob)).unwrap();
+		// The multisig account cannot afford the transfer, so the approval of bob is undone too.
+		assert_eq!(
+			runtime.dispatch(support::Origin::Signed(bob.clone()), approve(&alice)),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		assert_eq!(runtime.balances.balance(&charlie), 0);
+
+		// The approval of alice is still pending, so the approval of bob completes it once the
+		// multisig account has been funded.
+		runtime.balances.mint(&multi_account, 90).unwrap();
+		runtime.dispatch(support::Origin::Signed(bob.clone()), approve(&alice)).unwrap();
+		assert_eq!(runtime.balances.balance(&multi_account), 60);
+		assert_eq!(runtime.balances.balance(&charlie), 40);
+	}
+
+	#[test]
+	fn query_state() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		let mut runtime = Runtime::new_with_genesis(GenesisConfig {
+			balances: vec![(alice.clone(), 100)],
+			claims: vec![(bob.clone(), String::from("document"), String::new())],
+			..Default::default()
+		});
+		runtime
+			.execute_block(types::Block {
+				header: support::Header { block_number: 1 },
+				extrinsics: vec![support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 30,
+					}),
+				}],
+			})
+			.unwrap();
+
+		assert_eq!(runtime.query(RuntimeQuery::Balance(alice.clone())), QueryResult::Balance(69));
+		assert_eq!(runtime.query(RuntimeQuery::Balance(bob.clone())), QueryResult::Balance(30));
+		assert_eq!(runtime.query(RuntimeQuery::Nonce(alice)), QueryResult::Nonce(1));
+		assert_eq!(runtime.query(RuntimeQuery::Nonce(bob.clone())), QueryResult::Nonce(0));
+		assert_eq!(runtime.query(RuntimeQuery::BlockNumber), QueryResult::BlockNumber(1));
+		assert_eq!(
+			runtime.query(RuntimeQuery::ClaimOwner(content("document"))),
+			QueryResult::ClaimOwner(Some(bob))
+		);
+		assert_eq!(
+			runtime.query(RuntimeQuery::ClaimOwner(content("missing"))),
+			QueryResult::ClaimOwner(None)
+		);
+		// The fee of the transfer went into the pot.
+		assert_eq!(runtime.query(RuntimeQuery::TreasuryPot), QueryResult::TreasuryPot(1));
+		assert_eq!(
+			runtime.query(RuntimeQuery::VestingSchedule(AccountId::from_name("charlie"))),
+			QueryResult::VestingSchedule(None)
+		);
+		#[cfg(feature = "voting")]
+		assert_eq!(
+			runtime.query(RuntimeQuery::ProposalResult(0)),
+			QueryResult::ProposalResult(None)
+		);
+	}
+
+	#[test]
+	fn execute_block_with_fees() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		// Enough for the fee of a transfer, but not for the transfer itself.
+		runtime.balances.mint(&alice, 1).unwrap();
+
+		let call = RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 10 });
+		assert_eq!(call.fee(), 1);
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic { caller: alice.clone(), nonce: 1, call },
+				// Alice cannot pay the fee of another extrinsic, so it is skipped.
+				support::Extrinsic {
+					caller: alice.clone(),
+					nonce: 2,
+					call: RuntimeCall::balances(balances::Call::transfer {
+						to: bob.clone(),
+						amount: 0,
+					}),
+				},
+			],
+		};
+		let errors = runtime.execute_block_collect_errors(block).unwrap();
+
+		assert_eq!(
+			errors,
+			vec![
+				(0, support::DispatchError::InsufficientBalance),
+				(1, support::DispatchError::InsufficientBalance)
+			]
+		);
+		assert_eq!(runtime.system.nonce(&alice), 1);
+		assert_eq!(runtime.system.extrinsic_count(), 1);
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		// Fees are taken out of the balances, into the pot of the treasury. Only the block reward
+		// is left.
+		assert_eq!(runtime.balances.total_issuance(), 10);
+		assert_eq!(runtime.treasury.pot(), 1);
+	}
+
+	#[test]
+	fn treasury_spend() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let council = AccountId::from_name("council");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.mint(&council, 10).unwrap();
+
+		let transfer = |nonce| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 10 }),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![transfer(1), transfer(2), transfer(3)],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.treasury.pot(), 3);
+		// The fees are out of the total issuance, but the block reward is in.
+		assert_eq!(runtime.balances.total_issuance(), 117);
+
+		let spend = |caller: &AccountId, nonce| support::Extrinsic {
+			caller: caller.clone(),
+			nonce,
+			call: RuntimeCall::treasury(treasury::Call::spend { to: bob.clone(), amount: 2 }),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 2 },
+			extrinsics: vec![spend(&alice, 4), spend(&council, 1)],
+		};
+		let errors = runtime.execute_block_collect_errors(block).unwrap();
+
+		assert_eq!(errors, vec![(0, support::DispatchError::BadOrigin)]);
+		// Both spends paid a fee into the pot, and the second one took 2 out of it.
+		assert_eq!(runtime.treasury.pot(), 3);
+		assert_eq!(runtime.balances.balance(&bob), 32);
+		assert_eq!(runtime.balances.balance(&council), 9);
+		assert_eq!(runtime.balances.total_issuance(), 127);
+	}
+
+	#[test]
+	fn vested_transfer() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 200).unwrap();
+		runtime.balances.mint(&bob, 10).unwrap();
+
+		// 100 units, unlocking 10 per block after block 1.
+		let vested_transfer = RuntimeCall::vesting(vesting::Call::vested_transfer {
+			to: bob.clone(),
+			locked: 100,
+			per_block: 10,
+			start_block: 1,
+		});
+		let claim = |nonce| support::Extrinsic {
+			caller: bob.clone(),
+			nonce,
+			call: RuntimeCall::vesting(vesting::Call::claim_vested {}),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: vested_transfer,
+			}],
+		};
+		runtime.execute_block(block).unwrap();
+		// The locked funds are taken out of the balance of alice, and out of the total issuance.
+		assert_eq!(runtime.balances.balance(&alice), 99);
+		assert_eq!(runtime.balances.total_issuance(), 119);
+
+		let block =
+			types::Block { header: support::Header { block_number: 2 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		let block = types::Block {
+			header: support::Header { block_number: 3 },
+			extrinsics: vec![claim(1)],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.balances.balance(&bob), 29);
+
+		// A schedule cannot be funded with more than the caller has, so it is not added.
+		let vested_transfer = RuntimeCall::vesting(vesting::Call::vested_transfer {
+			to: alice.clone(),
+			locked: 1_000,
+			per_block: 10,
+			start_block: 1,
+		});
+		assert_eq!(
+			runtime.dispatch(support::Origin::Signed(bob.clone()), vested_transfer),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		assert!(runtime.vesting.schedule(&alice).is_none());
+		assert_eq!(runtime.check_invariants(), Ok(()));
+	}
+
+	#[test]
+	fn frozen_account_cannot_fund_vesting() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.freeze(&alice);
+
+		// Funding a schedule moves funds like a transfer, which a frozen account cannot make.
+		let vested_transfer = RuntimeCall::vesting(vesting::Call::vested_transfer {
+			to: bob.clone(),
+			locked: 100,
+			per_block: 100,
+			start_block: 0,
+		});
+		assert_eq!(
+			runtime.dispatch(support::Origin::Signed(alice.clone()), vested_transfer),
+			Err(support::DispatchError::AccountFrozen)
+		);
+		assert!(runtime.vesting.schedule(&bob).is_none());
+		assert_eq!(runtime.balances.balance(&alice), 100);
+	}
+
+	#[test]
+	#[cfg(feature = "voting")]
+	fn votes_weighed_when_cast() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.mint(&bob, 60).unwrap();
+
+		let create = RuntimeCall::voting(crate::voting::Call::create_proposal { proposal_id: 1 });
+		runtime.dispatch(support::Origin::Signed(alice.clone()), create).unwrap();
+		let aye = RuntimeCall::voting(crate::voting::Call::vote { proposal_id: 1, approve: true });
+		runtime.dispatch(support::Origin::Signed(alice.clone()), aye).unwrap();
+		let nay = RuntimeCall::voting(crate::voting::Call::vote { proposal_id: 1, approve: false });
+		runtime.dispatch(support::Origin::Signed(bob), nay).unwrap();
+		assert_eq!(runtime.voting.result(&1), Some(true));
+
+		// The funds alice moves after voting keep the weight of her vote.
+		runtime.balances.force_transfer(alice, charlie, 100).unwrap();
+		assert_eq!(runtime.voting.result(&1), Some(true));
+	}
+
+	#[test]
+	fn treasury_failed_payout() {
+		let mut runtime = Runtime::new();
+		let bob = AccountId::from_name("bob");
+		let council = AccountId::from_name("council");
+
+		runtime.treasury.deposit(10).unwrap();
+		runtime.balances.mint(&bob, types::Balance::MAX).unwrap();
+
+		// Paying bob would overflow his balance, so the spend is undone and the pot is kept.
+		let spend = RuntimeCall::treasury(treasury::Call::spend { to: bob.clone(), amount: 4 });
+		assert_eq!(
+			runtime.dispatch(support::Origin::Signed(council), spend),
+			Err(support::DispatchError::Overflow)
+		);
+		assert_eq!(runtime.treasury.pot(), 10);
+		assert_eq!(runtime.balances.balance(&bob), types::Balance::MAX);
+	}
+
+	#[test]
+	fn validate_extrinsic() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 10).unwrap();
+
+		let transfer = |nonce, amount| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount }),
+		};
+		assert_eq!(runtime.validate(&transfer(1, 5)), Ok(()));
+		assert_eq!(runtime.validate(&transfer(2, 5)), Err(support::DispatchError::InvalidNonce));
+		assert_eq!(
+			runtime.validate(&transfer(1, 20)),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		// Alice can pay the fee, but is then left with too little for the transfer.
+		assert_eq!(
+			runtime.validate(&transfer(1, 10)),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		assert_eq!(
+			runtime.clone().apply_extrinsic(transfer(1, 10)),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		let claim = support::Extrinsic {
+			caller: bob.clone(),
+			nonce: 1,
+			call: RuntimeCall::proof_of_existence(proof_of_existence::Call::revoke_claim {
+				claim: content("document"),
+			}),
+		};
+		// Bob cannot even pay the fee of the call.
+		assert_eq!(runtime.validate(&claim), Err(support::DispatchError::InsufficientBalance));
+
+		// Nothing was changed.
+		assert_eq!(runtime.system.nonce(&alice), 0);
+		assert_eq!(runtime.balances.balance(&alice), 10);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+
+		// The largest nonce has no next one.
+		runtime.system.set_nonce(&alice, types::Nonce::MAX);
+		assert_eq!(
+			runtime.validate(&transfer(types::Nonce::MAX, 5)),
+			Err(support::DispatchError::NonceOverflow)
+		);
+	}
+
+	#[test]
+	fn dispatch_transactional() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		// The second transfer fails, after the first one has been applied.
+		let batch = |transfers| RuntimeCall::balances(balances::Call::batch_transfer { transfers });
+		assert_eq!(
+			runtime.dispatch_transactional(
+				alice.clone(),
+				batch(vec![(bob.clone(), 60), (charlie.clone(), 60)])
+			),
+			Err(support::DispatchError::InsufficientBalance)
+		);
+		assert_eq!(runtime.balances.balance(&alice), 100);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert_eq!(runtime.balances.balance(&charlie), 0);
+		assert!(runtime.balances.events().is_empty());
+
+		assert_eq!(
+			runtime.dispatch_transactional(
+				alice.clone(),
+				batch(vec![(bob.clone(), 60), (charlie.clone(), 40)])
+			),
+			Ok(())
+		);
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.balances.balance(&bob), 60);
+		assert_eq!(runtime.balances.balance(&charlie), 40);
+	}
+
+	#[test]
+	fn block_reward() {
+		let mut runtime = Runtime::new();
+		let author = AccountId::from_name("author");
+
+		for block_number in 1..=3 {
+			let block =
+				types::Block { header: support::Header { block_number }, extrinsics: vec![] };
+			runtime.execute_block(block).unwrap();
+		}
+
+		assert_eq!(runtime.balances.balance(&author), 30);
+		assert_eq!(runtime.balances.total_issuance(), 30);
+	}
+
+	#[test]
+	fn event_phases() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		let transfer = |nonce, amount| support::Extrinsic {
+			caller: alice.clone(),
+			nonce,
+			call: RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount }),
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![transfer(1, 10), transfer(2, 20)],
+		};
+		runtime.execute_block(block).unwrap();
+
+		let records = runtime.system.event_records();
+		assert_eq!(records.len(), 2);
+		assert_eq!(records[0].phase, system::Phase::ApplyExtrinsic(0));
+		assert_eq!(records[1].phase, system::Phase::ApplyExtrinsic(1));
+		assert!(matches!(
+			records[1].event,
+			RuntimeEvent::balances(balances::Event::Transferred { amount: 20, .. })
+		));
+		assert_eq!(runtime.system.phase(), system::Phase::Finalization);
+	}
+
+	#[test]
+	fn dispatch_origin() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+
+		// Setting the timestamp is an inherent, so it cannot be signed.
+		let set = |now| RuntimeCall::timestamp(timestamp::Call::set { now });
+		assert_eq!(
+			runtime.dispatch(support::Origin::Signed(alice.clone()), set(1_000)),
+			Err(support::DispatchError::BadOrigin)
+		);
+		assert_eq!(runtime.timestamp.now(), 0);
+		assert_eq!(runtime.dispatch(support::Origin::None, set(1_000)), Ok(()));
+		assert_eq!(runtime.timestamp.now(), 1_000);
+
+		// A transfer must be signed by the account it is paid from.
+		let transfer =
+			RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 30 });
+		assert_eq!(
+			runtime.can_dispatch(&support::Origin::None, &transfer),
+			Err(support::DispatchError::BadOrigin)
+		);
+		assert_eq!(
+			runtime.dispatch(support::Origin::None, transfer.clone()),
+			Err(support::DispatchError::BadOrigin)
+		);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+		assert_eq!(runtime.dispatch(support::Origin::Signed(alice.clone()), transfer), Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 30);
+	}
+
+	#[test]
+	fn apply_extrinsic() {
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let transfer =
+			|amount| RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount });
+		let extrinsics = || {
+			vec![
+				support::Extrinsic { caller: alice.clone(), nonce: 1, call: transfer(20) },
+				support::Extrinsic { caller: alice.clone(), nonce: 2, call: transfer(30) },
+			]
+		};
+
+		let mut block_runtime = Runtime::new();
+		block_runtime.balances.mint(&alice, 100).unwrap();
+		let block =
+			types::Block { header: support::Header { block_number: 1 }, extrinsics: extrinsics() };
+		assert_eq!(block_runtime.execute_block_collect_errors(block), Ok(vec![]));
+
+		let mut runtime = Runtime::new();
+		runtime.balances.mint(&alice, 100).unwrap();
+		for extrinsic in extrinsics() {
+			assert_eq!(runtime.apply_extrinsic(extrinsic), Ok(()));
+		}
+
+		// The block number is not changed, but the extrinsics have the same effect as in a block.
+		assert_eq!(runtime.system.block_number(), 0);
+		assert_eq!(runtime.system.nonce(&alice), block_runtime.system.nonce(&alice));
+		assert_eq!(runtime.system.nonce(&alice), 2);
+		assert_eq!(runtime.balances.balance(&alice), block_runtime.balances.balance(&alice));
+		assert_eq!(runtime.balances.balance(&bob), block_runtime.balances.balance(&bob));
+		assert_eq!(runtime.balances.balance(&bob), 50);
+
+		// A replayed extrinsic is rejected.
+		let replay = support::Extrinsic { caller: alice.clone(), nonce: 2, call: transfer(30) };
+		assert_eq!(runtime.apply_extrinsic(replay), Err(support::DispatchError::InvalidNonce));
+	}
+
+	#[test]
+	fn check_invariants() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.reserve(&alice, 20).unwrap();
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: RuntimeCall::balances(balances::Call::transfer {
+					to: bob.clone(),
+					amount: 30,
+				}),
+			}],
+		};
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.check_invariants(), Ok(()));
+
+		// Setting a balance directly does not update the total issuance.
+		runtime.balances.set_balance(&bob, 60);
+		assert_eq!(
+			runtime.check_invariants(),
+			Err(String::from(
+				"free (119) and reserved (20) balances do not add up to the total issuance (109)"
+			))
+		);
+	}
+
+	#[test]
+	fn account_count() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.mint(&bob, 100).unwrap();
+		runtime.balances.clear_balance(&bob);
+
+		// The accounts are counted once the events of the block are collected. The author of the
+		// block is created by its reward.
+		let block =
+			types::Block { header: support::Header { block_number: 1 }, extrinsics: vec![] };
+		runtime.execute_block(block).unwrap();
+		assert_eq!(runtime.system.account_count(), 2);
+		assert_eq!(runtime.system.providers(&alice), 1);
+		assert_eq!(runtime.system.providers(&bob), 0);
+
+		// An account which pays away all of its balance is still kept alive by its nonce.
+		let block = types::Block {
+			header: support::Header { block_number: 2 },
+			extrinsics: vec![support::Extrinsic {
+				caller: alice.clone(),
+				nonce: 1,
+				call: RuntimeCall::balances(balances::Call::transfer {
+					to: bob.clone(),
+					amount: 99,
+				}),
+			}],
+		};
+		assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+		assert_eq!(runtime.balances.balance(&alice), 0);
+		assert_eq!(runtime.system.providers(&alice), 1);
+		assert_eq!(runtime.system.account_count(), 3);
+	}
+
+	#[test]
+	fn create_account() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+
+		assert_eq!(runtime.create_account(&alice), Ok(()));
+		assert!(runtime.system.account_exists(&alice));
+		assert!(runtime.balances.account_exists(&alice));
+		assert_eq!(runtime.create_account(&alice), Err(support::DispatchError::AccountExists));
+	}
+
+	#[test]
+	fn upgrade() {
+		use crate::support::Migrate;
+
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+
+		// The state of a new runtime is already up to date.
+		runtime.upgrade();
+		assert_eq!(runtime.proof_of_existence.storage_version(), 1);
+		assert_eq!(runtime.balances.storage_version(), 0);
+
+		// State from before version 1 of the proof of existence storage is migrated.
+		runtime.proof_of_existence.set_storage_version(0);
+		runtime
+			.proof_of_existence
+			.create_claim(alice.clone(), String::from("document"), String::new())
+			.unwrap();
+		runtime.upgrade();
+		assert_eq!(runtime.proof_of_existence.storage_version(), 1);
+		assert_eq!(runtime.proof_of_existence.get_claim(&content("document")), Some(&alice));
+	}
+
+	#[test]
+	fn pause_call() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		let transfer =
+			RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 30 });
+		assert_eq!(transfer.name(), ("balances", "transfer"));
+
+		runtime.pause_call("balances", "transfer");
+		let origin = support::Origin::Signed(alice.clone());
+		assert_eq!(
+			runtime.can_dispatch(&origin, &transfer),
+			Err(support::DispatchError::CallFiltered)
+		);
+		assert_eq!(
+			runtime.dispatch(origin.clone(), transfer.clone()),
+			Err(support::DispatchError::CallFiltered)
+		);
+		assert_eq!(runtime.balances.balance(&bob), 0);
+
+		// Other calls of the same pallet are not paused.
+		let burn = RuntimeCall::balances(balances::Call::burn { amount: 10 });
+		assert_eq!(runtime.dispatch(origin.clone(), burn), Ok(()));
+
+		runtime.resume_call("balances", "transfer");
+		assert_eq!(runtime.dispatch(origin, transfer), Ok(()));
+		assert_eq!(runtime.balances.balance(&bob), 30);
+	}
+
+	#[test]
+	fn metrics() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		assert_eq!(
+			runtime.metrics(),
+			RuntimeMetrics {
+				block_number: 0,
+				total_issuance: 0,
+				account_count: 0,
+				total_claims: 0,
+				events_this_block: 0,
+			}
+		);
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		let claim = |content: &str| {
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim: content.into(),
+				metadata: String::new(),
+			})
+		};
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![
+				support::Extrinsic { caller: alice.clone(), nonce: 1, call: claim("document") },
+				support::Extrinsic { caller: alice.clone(), nonce: 2, call: claim("record") },
+			],
+		};
+		runtime.execute_block(block).unwrap();
+
+		let metrics = runtime.metrics();
+		assert_eq!(metrics.block_number, 1);
+		// Alice paid a fee of 1 for each claim, and the author was rewarded 10.
+		assert_eq!(metrics.total_issuance, 108);
+		// Alice, and the author who was rewarded for the block.
+		assert_eq!(metrics.account_count, 2);
+		assert_eq!(metrics.total_claims, 2);
+		// One `ClaimCreated` event for each claim.
+		assert_eq!(metrics.events_this_block, 2);
+	}
+
+	#[test]
+	fn diff() {
+		let mut runtime = Runtime::new();
+		let alice = AccountId::from_name("alice");
+		let bob = AccountId::from_name("bob");
+		let charlie = AccountId::from_name("charlie");
+
+		runtime.balances.mint(&alice, 100).unwrap();
+		runtime.balances.mint(&charlie, 100).unwrap();
+		let before = runtime.clone();
+		assert_eq!(before.diff(&runtime), StateDiff::default());
+
+		let transfer =
+			RuntimeCall::balances(balances::Call::transfer { to: bob.clone(), amount: 30 });
+		runtime.dispatch(support::Origin::Signed(alice.clone()), transfer).unwrap();
+
+		let diff = before.diff(&runtime);
+		assert_eq!(
+			diff.balances.into_iter().collect::<Vec<_>>(),
+			vec![(alice.clone(), (100, 70)), (bob.clone(), (0, 30))]
+		);
+		assert!(diff.nonces.is_empty());
+		assert!(diff.added_claims.is_empty());
+
+		// A block changes nonces, and claims are added and removed.
+		let before = runtime.clone();
+		let block = types::Block {
+			header: support::Header { block_number: 1 },
+			extrinsics: vec![support::Extrinsic {
+				caller: charlie.clone(),
+				nonce: 1,
+				call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+					claim: String::from("document"),
+					metadata: String::new(),
+				}),
+			}],
+		};
+		runtime.execute_block(block).unwrap();
+
+		let diff = before.diff(&runtime);
+		assert_eq!(diff.nonces.into_iter().collect::<Vec<_>>(), vec![(charlie.clone(), (0, 1))]);
+		assert_eq!(diff.added_claims, vec![content("document")]);
+		assert_eq!(runtime.diff(&before).removed_claims, vec![content("document")]);
+	}
+
+	// Declare the `types` of a small test runtime, with `$account_id` accounts, and configure the
+	// system and balances pallets for it, so each of the test runtimes below only has to list its
+	// pallets. The caller of each extrinsic pays a fee of one unit per unit of weight.
+	macro_rules! test_runtime_types {
+		(AccountId = $account_id:ty, RuntimeEvent = $runtime_event:ty) => {
+			#[allow(dead_code)]
+			mod types {
+				use super::RuntimeCall;
+				use crate::support;
+
+				pub type AccountId = $account_id;
+				pub type BlockNumber = u32;
+				pub type Nonce = u32;
+				pub type Extrinsic = support::Extrinsic<AccountId, Nonce, RuntimeCall>;
+				pub type Header = support::Header<BlockNumber>;
+				pub type Block = support::Block<Header, Extrinsic>;
+			}
+
+			impl crate::system::Config for Runtime {
+				type AccountId = types::AccountId;
+				type BlockNumber = types::BlockNumber;
+				type Nonce = types::Nonce;
+				type RuntimeEvent = $runtime_event;
+				type Hash = u64;
+			}
+
+			impl crate::balances::Config for Runtime {
+				type Balance = u128;
+
+				fn weight_to_fee(weight: crate::support::Weight) -> Self::Balance {
+					weight.units().into()
+				}
+			}
+		};
+	}
+
+	// A runtime whose accounts are 32 byte arrays, like public keys, instead of strings.
+	mod byte_accounts {
+		use crate::{balances, proof_of_existence, support, system};
+
+		test_runtime_types!(AccountId = [u8; 32], RuntimeEvent = RuntimeEvent);
+
+		#[derive(Debug, Clone)]
+		#[macros::runtime]
+		pub struct Runtime {
+			system: system::Pallet<Self>,
+			#[pallet(currency)]
+			balances: balances::Pallet<Self>,
+			proof_of_existence: proof_of_existence::Pallet<Self>,
+		}
+
+		#[derive(Debug, Clone)]
+		#[allow(non_camel_case_types, dead_code)]
+		pub enum RuntimeEvent {
+			balances(balances::Event<Runtime>),
+			proof_of_existence(proof_of_existence::Event<Runtime>),
+		}
+
+		impl From<balances::Event<Runtime>> for RuntimeEvent {
+			fn from(event: balances::Event<Runtime>) -> Self {
+				Self::balances(event)
+			}
+		}
+
+		impl From<proof_of_existence::Event<Runtime>> for RuntimeEvent {
+			fn from(event: proof_of_existence::Event<Runtime>) -> Self {
+				Self::proof_of_existence(event)
+			}
+		}
+
+		impl proof_of_existence::Config for Runtime {
+			type RawContent = [u8; 32];
+			type Content = [u8; 32];
+			type Metadata = ();
+
+			fn max_claims() -> u32 {
+				10
+			}
+
+			fn hash(content: &Self::RawContent) -> Self::Content {
+				*content
+			}
+		}
+
+		#[test]
+		fn transfer() {
+			let alice = [1; 32];
+			let bob = [2; 32];
+
+			let mut runtime = Runtime::new();
+			runtime.balances.mint(&alice, 100).unwrap();
+
+			let block = types::Block {
+				header: support::Header { block_number: 1 },
+				extrinsics: vec![
+					support::Extrinsic {
+						caller: alice,
+						nonce: 1,
+						call: RuntimeCall::balances(balances::Call::transfer {
+							to: bob,
+							amount: 30,
+						}),
+					},
+					support::Extrinsic {
+						caller: bob,
+						nonce: 1,
+						call: RuntimeCall::proof_of_existence(
+							proof_of_existence::Call::create_claim { claim: [3; 32], metadata: () },
+						),
+					},
+				],
+			};
+			assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+
+			// Each extrinsic paid a fee of 1.
+			assert_eq!(runtime.balances.balance(&alice), 69);
+			assert_eq!(runtime.balances.balance(&bob), 29);
+			assert_eq!(runtime.system.nonce(&alice), 1);
+			assert_eq!(runtime.proof_of_existence.get_claim(&[3; 32]), Some(&bob));
+		}
+	}
+
+	// A minimal runtime, with only the system and balances pallets.
+	mod minimal {
+		use crate::{balances, support, system};
+
+		test_runtime_types!(AccountId = u32, RuntimeEvent = balances::Event<Self>);
+
+		#[derive(Debug, Clone)]
+		#[macros::runtime]
+		pub struct Runtime {
+			system: system::Pallet<Self>,
+			#[pallet(currency)]
+			balances: balances::Pallet<Self>,
+			// This pallet is left out, like the pallets whose feature is turned off.
+			#[cfg(any())]
+			proof_of_existence: crate::proof_of_existence::Pallet<Self>,
+		}
+
+		#[test]
+		fn transfer() {
+			let (alice, bob) = (1, 2);
+			let mut runtime = Runtime::new();
+			runtime.balances.mint(&alice, 100).unwrap();
+
+			let block = types::Block {
+				header: support::Header { block_number: 1 },
+				extrinsics: vec![support::Extrinsic {
+					caller: alice,
+					nonce: 1,
+					call: RuntimeCall::balances(balances::Call::transfer { to: bob, amount: 30 }),
+				}],
+			};
+			assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+
+			// Alice paid a fee of 1 for the transfer.
+			assert_eq!(runtime.balances.balance(&alice), 69);
+			assert_eq!(runtime.balances.balance(&bob), 30);
+			assert_eq!(runtime.system.nonce(&alice), 1);
+			assert!(matches!(
+				runtime.system.events()[..],
+				[balances::Event::Transferred {
+					from: 1,
+					to: 2,
+					amount: 30,
+					from_balance_after: 69,
+					to_balance_after: 30,
+				}]
+			));
+		}
+	}
+
+	mod signed_extensions {
+		// The currency is found by its role, so its field does not have to be named `balances`.
+		use crate::{balances, balances as money, support, system};
+
+		test_runtime_types!(AccountId = u32, RuntimeEvent = balances::Event<Self>);
+
+		// A call filter, which rejects every burn.
+		#[derive(Default)]
+		pub struct NoBurns;
+
+		impl support::SignedExtension<Runtime, types::AccountId, RuntimeCall> for NoBurns {
+			fn validate(
+				&self,
+				_runtime: &Runtime,
+				_caller: &types::AccountId,
+				call: &RuntimeCall,
+			) -> support::DispatchResult {
+				match call {
+					RuntimeCall::money(balances::Call::burn { .. }) =>
+						Err(support::DispatchError::CallFiltered),
+					_ => Ok(()),
+				}
+			}
+		}
+
+		#[derive(Debug, Clone)]
+		#[macros::runtime(extensions = (NoBurns))]
+		pub struct Runtime {
+			system: system::Pallet<Self>,
+			#[pallet(currency)]
+			money: money::Pallet<Self>,
+		}
+
+		fn extrinsic(caller: types::AccountId, nonce: types::Nonce) -> types::Extrinsic {
+			let call = RuntimeCall::money(balances::Call::transfer { to: 3, amount: 10 });
+			support::Extrinsic { caller, nonce, call }
+		}
+
+		#[test]
+		fn pipeline() {
+			let (alice, bob) = (1, 2);
+			let mut runtime = Runtime::new();
+			runtime.money.mint(&alice, 100).unwrap();
+
+			// Bob cannot pay the fee, but the nonce is checked first, so the pipeline stops there.
+			assert_eq!(
+				runtime.apply_extrinsic(extrinsic(bob, 2)),
+				Err(support::DispatchError::InvalidNonce)
+			);
+			assert_eq!(
+				runtime.apply_extrinsic(extrinsic(bob, 1)),
+				Err(support::DispatchError::InsufficientBalance)
+			);
+			assert_eq!(runtime.system.nonce(&bob), 0);
+
+			// The extra extension runs after the built-in ones, and filters burns.
+			let burn = support::Extrinsic {
+				caller: alice,
+				nonce: 1,
+				call: RuntimeCall::money(balances::Call::burn { amount: 10 }),
+			};
+			assert_eq!(runtime.apply_extrinsic(burn), Err(support::DispatchError::CallFiltered));
+			assert_eq!(runtime.apply_extrinsic(extrinsic(alice, 1)), Ok(()));
+			assert_eq!(runtime.system.nonce(&alice), 1);
+			assert_eq!(runtime.money.balance(&3), 10);
+		}
+	}
+
+	mod new_accounts {
+		use crate::{balances, support, system};
+
+		test_runtime_types!(AccountId = u32, RuntimeEvent = balances::Event<Self>);
+
+		// A pallet which counts how many times each account was welcomed by `OnNewAccount`.
+		mod welcome {
+			use crate::support::DispatchResult;
+			use std::collections::BTreeMap;
+
+			pub trait Config: crate::system::Config {}
+
+			#[derive(Debug, Clone)]
+			pub struct Pallet<T: Config> {
+				pub welcomed: BTreeMap<T::AccountId, u32>,
+			}
+
+			impl<T: Config> Pallet<T> {
+				pub fn new() -> Self {
+					Self { welcomed: BTreeMap::new() }
+				}
+			}
+
+			impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+			impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+			impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {
+				fn on_new_account(&mut self, who: &T::AccountId) {
+					*self.welcomed.entry(who.clone()).or_default() += 1;
+				}
+			}
+
+			impl<T: Config> crate::support::Migrate for Pallet<T> {
+				fn storage_version(&self) -> u16 {
+					0
+				}
+
+				fn set_storage_version(&mut self, _version: u16) {}
+			}
+
+			impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+			// The runtime needs a call for every pallet, so this one does nothing.
+			#[macros::call]
+			impl<T: Config> Pallet<T> {
+				pub fn welcome(
+					&mut self,
+					_caller: T::AccountId,
+					_who: T::AccountId,
+				) -> DispatchResult {
+					Ok(())
+				}
+			}
+		}
+
+		#[derive(Debug, Clone)]
+		#[macros::runtime]
+		pub struct Runtime {
+			system: system::Pallet<Self>,
+			#[pallet(currency)]
+			balances: balances::Pallet<Self>,
+			welcome: welcome::Pallet<Self>,
+		}
+
+		impl welcome::Config for Runtime {}
+
+		#[test]
+		fn on_new_account() {
+			let (alice, bob) = (1, 2);
+			let mut runtime = Runtime::new();
+			runtime.balances.mint(&alice, 100).unwrap();
+
+			for block_number in 1..=2 {
+				let block = types::Block {
+					header: support::Header { block_number },
+					extrinsics: vec![support::Extrinsic {
+						caller: alice,
+						nonce: block_number,
+						call: RuntimeCall::balances(balances::Call::transfer {
+							to: bob,
+							amount: 10,
+						}),
+					}],
+				};
+				assert_eq!(runtime.execute_block_collect_errors(block), Ok(vec![]));
+			}
+
+			// Each account is welcomed once, when it first comes into existence, however many
+			// deposits and transactions it has afterwards.
+			assert_eq!(runtime.welcome.welcomed.get(&alice), Some(&1));
+			assert_eq!(runtime.welcome.welcomed.get(&bob), Some(&1));
+			assert_eq!(runtime.welcome.welcomed.len(), 2);
+		}
+	}
+}
@@ -0,0 +1,301 @@
+use core::hash::Hash;
+use std::collections::BTreeSet;
+
+use crate::support::{DispatchError, DispatchResult, StorageMap};
+
+pub trait Config: crate::system::Config {
+	/// The aggregated call type of the runtime, which is dispatched once enough signatories have
+	/// approved it.
+	type RuntimeCall: Hash;
+
+	/// Get the account on behalf of which the calls approved by the `signatories` are dispatched,
+	/// when `threshold` of them are needed to approve a call. The `signatories` are sorted.
+	fn multi_account_id(signatories: &[Self::AccountId], threshold: u16) -> Self::AccountId;
+
+	/// Get the hash which identifies a call awaiting approval. Since it is stored, it must always
+	/// be the same for the same call, whichever build of the runtime computes it.
+	fn hash_call(call: &Self::RuntimeCall) -> Self::Hash;
+}
+
+/// The key of a call awaiting approval: the sorted signatories of the multisig, and the hash of the
+/// call.
+pub type MultisigKey<AccountId, Hash> = (Vec<AccountId>, Hash);
+
+/// A storage map from each call awaiting approval to its approvals so far.
+pub type PendingCalls<AccountId, Hash> =
+	StorageMap<MultisigKey<AccountId, Hash>, Multisig<AccountId>>;
+
+/// A call awaiting the approval of enough signatories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "AccountId: serde::Serialize",
+		deserialize = "AccountId: Ord + serde::Deserialize<'de>",
+	))
+)]
+pub struct Multisig<AccountId> {
+	/// The number of approvals needed to dispatch the call.
+	pub threshold: u16,
+	/// The signatories which have approved the call so far.
+	pub approvals: BTreeSet<AccountId>,
+}
+
+/// This is the Multisig Module.
+/// It lets a group of signatories dispatch a call from a shared account, once a threshold of them
+/// have approved it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::Hash: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, T::Hash: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A storage map from the sorted signatories and the hash of a call, to the approvals of that
+	/// call.
+	pending: PendingCalls<T::AccountId, T::Hash>,
+	/// The call which has just reached its threshold, with the multisig account on behalf of which
+	/// it is dispatched. The runtime takes it right after the approval is dispatched.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	ready: Option<(T::AccountId, T::RuntimeCall)>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Multisig Module.
+	pub fn new() -> Self {
+		Self {
+			pending: StorageMap::new(),
+			ready: None,
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the hash which identifies the `call`, as given by `Config::hash_call`.
+	pub fn call_hash(call: &T::RuntimeCall) -> T::Hash {
+		T::hash_call(call)
+	}
+
+	/// Get the pending approvals (if any) of the call with `call_hash`, by the `signatories`.
+	#[cfg(test)]
+	pub fn pending(
+		&self,
+		signatories: &[T::AccountId],
+		call_hash: T::Hash,
+	) -> Option<&Multisig<T::AccountId>> {
+		let mut signatories = signatories.to_vec();
+		signatories.sort();
+		self.pending.get(&(signatories, call_hash))
+	}
+
+	/// Take the call which has reached its threshold, with the multisig account on behalf of
+	/// which it should be dispatched.
+	pub fn take_ready_call(&mut self) -> Option<(T::AccountId, T::RuntimeCall)> {
+		self.ready.take()
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Approve the `call` on behalf of the `caller`, as one of the signatories of a multisig made
+	/// of the `caller` and the `other_signatories`.
+	/// Once `threshold` distinct signatories have approved the call, it is ready to be dispatched
+	/// on behalf of the multisig account. Approving the same call twice does nothing.
+	/// This function will return an error if the `caller` is listed in the `other_signatories`,
+	/// if the `threshold` cannot be reached by the signatories, or if it differs from the
+	/// threshold of the pending approvals.
+	// The call is boxed, since the `RuntimeCall` contains this `Call` and would otherwise have an
+	// infinite size.
+	#[allow(clippy::boxed_local)]
+	pub fn approve_as_multi(
+		&mut self,
+		caller: T::AccountId,
+		threshold: u16,
+		other_signatories: Vec<T::AccountId>,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		if other_signatories.contains(&caller) {
+			return Err(DispatchError::SenderInSignatories);
+		}
+		let mut signatories = other_signatories;
+		signatories.push(caller.clone());
+		signatories.sort();
+		signatories.dedup();
+		if threshold == 0 || usize::from(threshold) > signatories.len() {
+			return Err(DispatchError::InvalidThreshold);
+		}
+
+		let key = (signatories, Self::call_hash(&call));
+		let mut multisig = match self.pending.get(&key) {
+			Some(multisig) if multisig.threshold != threshold =>
+				return Err(DispatchError::InvalidThreshold),
+			Some(multisig) => multisig.clone(),
+			None => Multisig { threshold, approvals: BTreeSet::new() },
+		};
+		multisig.approvals.insert(caller);
+
+		if multisig.approvals.len() < usize::from(threshold) {
+			self.pending.insert(key, multisig);
+		} else {
+			self.pending.remove(&key);
+			let multi_account = T::multi_account_id(&key.0, threshold);
+			self.ready = Some((multi_account, *call));
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use core::hash::{Hash, Hasher};
+
+	use crate::support::DispatchError;
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	impl super::Config for TestConfig {
+		type RuntimeCall = &'static str;
+
+		fn multi_account_id(_signatories: &[Self::AccountId], _threshold: u16) -> Self::AccountId {
+			"multisig"
+		}
+
+		fn hash_call(call: &Self::RuntimeCall) -> Self::Hash {
+			let mut hasher = crate::support::StableHasher::default();
+			call.hash(&mut hasher);
+			hasher.finish()
+		}
+	}
+
+	type Multisig = super::Pallet<TestConfig>;
+
+	#[test]
+	fn two_of_three() {
+		let mut multisig = Multisig::new();
+		let call_hash = Multisig::call_hash(&"call");
+
+		assert_eq!(
+			multisig.approve_as_multi("alice", 2, vec!["bob", "charlie"], Box::new("call")),
+			Ok(())
+		);
+		assert_eq!(
+			multisig
+				.pending(&["alice", "bob", "charlie"], call_hash)
+				.unwrap()
+				.approvals
+				.len(),
+			1
+		);
+		assert!(multisig.take_ready_call().is_none());
+
+		// Approving twice is idempotent.
+		assert_eq!(
+			multisig.approve_as_multi("alice", 2, vec!["charlie", "bob"], Box::new("call")),
+			Ok(())
+		);
+		assert_eq!(
+			multisig
+				.pending(&["charlie", "bob", "alice"], call_hash)
+				.unwrap()
+				.approvals
+				.len(),
+			1
+		);
+		assert!(multisig.take_ready_call().is_none());
+
+		// The second distinct approval reaches the threshold.
+		assert_eq!(
+			multisig.approve_as_multi("charlie", 2, vec!["alice", "bob"], Box::new("call")),
+			Ok(())
+		);
+		assert!(multisig.pending(&["alice", "bob", "charlie"], call_hash).is_none());
+		assert_eq!(multisig.take_ready_call(), Some(("multisig", "call")));
+		assert!(multisig.take_ready_call().is_none());
+	}
+
+	#[test]
+	fn non_signatory_approval() {
+		let mut multisig = Multisig::new();
+		let call_hash = Multisig::call_hash(&"call");
+
+		multisig
+			.approve_as_multi("alice", 2, vec!["bob", "charlie"], Box::new("call"))
+			.unwrap();
+
+		// The caller cannot also be one of the other signatories.
+		assert_eq!(
+			multisig.approve_as_multi("dave", 2, vec!["alice", "dave"], Box::new("call")),
+			Err(DispatchError::SenderInSignatories)
+		);
+
+		// Another set of signatories is another multisig, so it does not count towards the
+		// approvals of the first one.
+		multisig
+			.approve_as_multi("dave", 2, vec!["alice", "bob"], Box::new("call"))
+			.unwrap();
+		assert_eq!(
+			multisig
+				.pending(&["alice", "bob", "charlie"], call_hash)
+				.unwrap()
+				.approvals
+				.len(),
+			1
+		);
+		assert!(multisig.take_ready_call().is_none());
+	}
+
+	#[test]
+	fn invalid_threshold() {
+		let mut multisig = Multisig::new();
+
+		assert_eq!(
+			multisig.approve_as_multi("alice", 0, vec!["bob"], Box::new("call")),
+			Err(DispatchError::InvalidThreshold)
+		);
+		assert_eq!(
+			multisig.approve_as_multi("alice", 3, vec!["bob"], Box::new("call")),
+			Err(DispatchError::InvalidThreshold)
+		);
+
+		multisig
+			.approve_as_multi("alice", 2, vec!["bob", "charlie"], Box::new("call"))
+			.unwrap();
+		assert_eq!(
+			multisig.approve_as_multi("bob", 3, vec!["alice", "charlie"], Box::new("call")),
+			Err(DispatchError::InvalidThreshold)
+		);
+	}
+}
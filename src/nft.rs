@@ -0,0 +1,194 @@
+use crate::support::{DispatchError, DispatchResult, StorageMap};
+
+pub trait Config: crate::system::Config {
+	/// The type used to identify each unique item.
+	type ItemId: Ord + Clone;
+}
+
+/// This is the NFT Module.
+/// It keeps track of which account owns each unique item. Unlike claims, items can be transferred
+/// between accounts, and the items of an account can be listed.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::ItemId: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, T::ItemId: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A storage map from each item to the account which owns it.
+	owners: StorageMap<T::ItemId, T::AccountId>,
+	/// A storage map from each account to the items it owns, in the order it got them.
+	items_of: StorageMap<T::AccountId, Vec<T::ItemId>>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the NFT Module.
+	pub fn new() -> Self {
+		Self {
+			owners: StorageMap::new(),
+			items_of: StorageMap::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the owner (if any) of an item.
+	pub fn owner_of(&self, item: &T::ItemId) -> Option<&T::AccountId> {
+		self.owners.get(item)
+	}
+
+	/// Get the items owned by the account `who`, in the order it got them.
+	#[cfg(test)]
+	pub fn items_of(&self, who: &T::AccountId) -> &[T::ItemId] {
+		self.items_of.get(who).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	// Add `item` to the items owned by `who`.
+	fn add_item(&mut self, who: T::AccountId, item: T::ItemId) {
+		match self.items_of.get_mut(&who) {
+			Some(items) => items.push(item),
+			None => {
+				self.items_of.insert(who, vec![item]);
+			},
+		}
+	}
+
+	// Remove `item` from the items owned by `who`, removing the account from the index once it
+	// owns no items.
+	fn remove_item(&mut self, who: &T::AccountId, item: &T::ItemId) {
+		if let Some(items) = self.items_of.get_mut(who) {
+			items.retain(|owned| owned != item);
+			if items.is_empty() {
+				self.items_of.remove(who);
+			}
+		}
+	}
+
+	// Check that the `item` exists, and that it is owned by the `caller`.
+	fn ensure_owner(&self, caller: &T::AccountId, item: &T::ItemId) -> DispatchResult {
+		match self.owner_of(item) {
+			Some(owner) if owner == caller => Ok(()),
+			Some(_) => Err(DispatchError::NotOwner),
+			None => Err(DispatchError::ItemNotFound),
+		}
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Mint a new `item`, owned by the `caller`.
+	/// This function will return an error if the item already exists.
+	pub fn mint(&mut self, caller: T::AccountId, item: T::ItemId) -> DispatchResult {
+		if self.owners.contains_key(&item) {
+			return Err(DispatchError::ItemAlreadyExists);
+		}
+		self.owners.insert(item.clone(), caller.clone());
+		self.add_item(caller, item);
+		Ok(())
+	}
+
+	/// Transfer the `item` owned by the `caller` to the account `to`.
+	/// This function will return an error if the item does not exist, or if the caller does not
+	/// own it.
+	pub fn transfer(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		item: T::ItemId,
+	) -> DispatchResult {
+		self.ensure_owner(&caller, &item)?;
+		self.remove_item(&caller, &item);
+		self.owners.insert(item.clone(), to.clone());
+		self.add_item(to, item);
+		Ok(())
+	}
+
+	/// Burn the `item` owned by the `caller`.
+	/// This function will return an error if the item does not exist, or if the caller does not
+	/// own it.
+	pub fn burn(&mut self, caller: T::AccountId, item: T::ItemId) -> DispatchResult {
+		self.ensure_owner(&caller, &item)?;
+		self.remove_item(&caller, &item);
+		self.owners.remove(&item);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::support::DispatchError;
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	impl super::Config for TestConfig {
+		type ItemId = u32;
+	}
+
+	#[test]
+	fn mint_and_transfer() {
+		let mut nft = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(nft.mint("alice", 1), Ok(()));
+		assert_eq!(nft.mint("alice", 2), Ok(()));
+		assert_eq!(nft.mint("bob", 1), Err(DispatchError::ItemAlreadyExists));
+		assert_eq!(nft.items_of(&"alice"), &[1, 2]);
+
+		assert_eq!(nft.transfer("bob", "bob", 1), Err(DispatchError::NotOwner));
+		assert_eq!(nft.transfer("alice", "bob", 3), Err(DispatchError::ItemNotFound));
+		assert_eq!(nft.transfer("alice", "bob", 1), Ok(()));
+
+		assert_eq!(nft.owner_of(&1), Some(&"bob"));
+		assert_eq!(nft.owner_of(&2), Some(&"alice"));
+		assert_eq!(nft.items_of(&"alice"), &[2]);
+		assert_eq!(nft.items_of(&"bob"), &[1]);
+	}
+
+	#[test]
+	fn burn() {
+		let mut nft = super::Pallet::<TestConfig>::new();
+
+		nft.mint("alice", 1).unwrap();
+
+		assert_eq!(nft.burn("bob", 1), Err(DispatchError::NotOwner));
+		assert_eq!(nft.burn("alice", 1), Ok(()));
+		assert_eq!(nft.owner_of(&1), None);
+		assert!(nft.items_of(&"alice").is_empty());
+		assert_eq!(nft.burn("alice", 1), Err(DispatchError::ItemNotFound));
+
+		// A burned item can be minted again.
+		assert_eq!(nft.mint("bob", 1), Ok(()));
+		assert_eq!(nft.owner_of(&1), Some(&"bob"));
+	}
+}
@@ -1,73 +1,557 @@
 use core::fmt::Debug;
-use std::collections::BTreeMap;
+use num::{CheckedAdd, Zero};
+use std::collections::BTreeSet;
 
-use crate::support::DispatchResult;
+use crate::support::{DispatchError, DispatchResult, StorageMap, TakeEvents};
 
 pub trait Config: crate::system::Config {
-	/// The type which represents the content that can be claimed using this pallet.
-	/// Could be the content directly as bytes, or better yet the hash of that content.
-	/// We leave that decision to the runtime developer.
-	type Content: Debug + Ord;
+	/// The type of the content submitted to be claimed, before it is hashed.
+	type RawContent;
+	/// The type which represents the content that is stored for a claim: the hash of the raw
+	/// content, so large payloads do not bloat the storage.
+	type Content: Debug + Ord + Clone;
+	/// The type of the data which can be attached to a claim, for example a short note.
+	type Metadata: Debug + Clone;
+
+	/// The maximum number of claims a single account can own at the same time.
+	fn max_claims() -> u32;
+
+	/// Hash the raw `content` into the content which is stored for its claim.
+	fn hash(content: &Self::RawContent) -> Self::Content;
+
+	/// The content which a claim stored before storage version 1 is stored under from version 1,
+	/// for example after a change of the hash function. By default, the content is kept as is.
+	fn migrate_content(content: &Self::Content) -> Self::Content {
+		content.clone()
+	}
+}
+
+/// The events emitted by the proof of existence module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<T: Config> {
+	/// The `owner` claimed the `content`.
+	ClaimCreated { owner: T::AccountId, content: T::Content },
+	/// The claim of the `owner` on the `content` was revoked.
+	ClaimRevoked { owner: T::AccountId, content: T::Content },
 }
 
 /// This is the Proof of Existence Module.
 /// It is a simple module that allows accounts to claim existence of some data.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::BlockNumber: serde::Serialize, \
+			T::Content: serde::Serialize, T::Metadata: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, \
+			T::BlockNumber: serde::Deserialize<'de>, T::Content: serde::Deserialize<'de>, \
+			T::Metadata: serde::Deserialize<'de>",
+	))
+)]
 pub struct Pallet<T: Config> {
-	/// A simple storage map from content to the owner of that content.
+	/// A simple storage map from content to the owner of that content, and the metadata attached
+	/// to the claim.
 	/// Accounts can make multiple different claims, but each claim can only have one owner.
-	claims: BTreeMap<T::Content, T::AccountId>,
+	claims: StorageMap<T::Content, (T::AccountId, T::Metadata)>,
+	/// A storage map from content to the block number at which it was claimed.
+	claim_block: StorageMap<T::Content, T::BlockNumber>,
+	/// A storage map from content to the account which its owner has authorized to revoke the
+	/// claim on their behalf.
+	revocation_delegates: StorageMap<T::Content, T::AccountId>,
+	/// A storage map from content to the block number at which its claim expires, for the claims
+	/// which were made with an expiry.
+	expiry: StorageMap<T::Content, T::BlockNumber>,
+	/// The current block number, which is noted at the start of each block.
+	block_number: T::BlockNumber,
+	/// The events emitted by this module which have not yet been collected by the runtime.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	events: Vec<Event<T>>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
 }
 
 impl<T: Config> Pallet<T> {
 	/// Create a new instance of the Proof of Existence Module.
 	pub fn new() -> Self {
-		Pallet { claims: BTreeMap::new() }
+		Pallet {
+			claims: StorageMap::new(),
+			claim_block: StorageMap::new(),
+			revocation_delegates: StorageMap::new(),
+			expiry: StorageMap::new(),
+			block_number: T::BlockNumber::zero(),
+			events: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
 	}
 
 	/// Get the owner (if any) of a claim.
 	pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
-		self.claims.get(claim)
+		self.claims.get(claim).map(|(owner, _)| owner)
+	}
+
+	/// Get a clone of the owner (if any) of a claim, which does not borrow the pallet, so it can be
+	/// changed while the owner is held.
+	#[cfg(test)]
+	pub fn claim_owner(&self, claim: &T::Content) -> Option<T::AccountId> {
+		self.get_claim(claim).cloned()
+	}
+
+	/// Check whether the content has been claimed, without looking up its owner.
+	pub fn contains_claim(&self, claim: &T::Content) -> bool {
+		self.claims.contains_key(claim)
+	}
+
+	/// Get the metadata (if any) attached to a claim.
+	#[cfg(test)]
+	pub fn claim_metadata(&self, claim: &T::Content) -> Option<&T::Metadata> {
+		self.claims.get(claim).map(|(_, metadata)| metadata)
+	}
+
+	/// Get all the content claimed by `who`, in sorted order.
+	pub fn claims_of(&self, who: &T::AccountId) -> Vec<&T::Content> {
+		self.claims
+			.iter()
+			.filter(|(_, (owner, _))| owner == who)
+			.map(|(claim, _)| claim)
+			.collect()
+	}
+
+	/// Iterate over all the claims and their owner, in sorted content order.
+	pub fn iter_claims(&self) -> impl Iterator<Item = (&T::Content, &T::AccountId)> {
+		self.claims.iter().map(|(claim, (owner, _))| (claim, owner))
+	}
+
+	/// Get the total number of claims.
+	pub fn total_claims(&self) -> usize {
+		self.claims.len()
+	}
+
+	/// Get the number of claims owned by `who`.
+	pub fn claim_count(&self, who: &T::AccountId) -> usize {
+		self.claims.values().filter(|(owner, _)| owner == who).count()
+	}
+
+	/// Get the block number (if any) at which a claim was created.
+	#[cfg(test)]
+	pub fn claim_block(&self, claim: &T::Content) -> Option<T::BlockNumber> {
+		self.claim_block.get(claim).copied()
+	}
+
+	/// Get the block number (if any) at which a claim expires, and is removed.
+	#[cfg(test)]
+	pub fn claim_expiry(&self, claim: &T::Content) -> Option<T::BlockNumber> {
+		self.expiry.get(claim).copied()
+	}
+
+	/// Get the account (if any) which the owner of a claim has authorized to revoke it.
+	pub fn revoker(&self, claim: &T::Content) -> Option<&T::AccountId> {
+		self.revocation_delegates.get(claim)
+	}
+
+	/// Check whether the `caller` could create the `claim`, without changing any state.
+	pub fn can_create_claim(
+		&self,
+		caller: &T::AccountId,
+		claim: &T::RawContent,
+		_metadata: &T::Metadata,
+	) -> DispatchResult {
+		if self.contains_claim(&T::hash(claim)) {
+			return Err(DispatchError::ClaimAlreadyExists);
+		};
+		if self.claim_count(caller) >= T::max_claims() as usize {
+			return Err(DispatchError::TooManyClaims);
+		}
+		Ok(())
+	}
+
+	/// Check whether the `caller` could create the `claim` with an expiry, without changing any
+	/// state.
+	pub fn can_create_claim_with_expiry(
+		&self,
+		caller: &T::AccountId,
+		claim: &T::RawContent,
+		metadata: &T::Metadata,
+		ttl: &T::BlockNumber,
+	) -> DispatchResult {
+		self.block_number.checked_add(ttl).ok_or(DispatchError::BlockNumberOverflow)?;
+		self.can_create_claim(caller, claim, metadata)
+	}
+
+	/// Check whether the `caller` could create all of the `claims`, one after the other, without
+	/// changing any state. The claims made earlier in the batch are taken into account.
+	pub fn can_create_claims(
+		&self,
+		caller: &T::AccountId,
+		claims: &[(T::RawContent, T::Metadata)],
+	) -> DispatchResult {
+		let mut batch = BTreeSet::new();
+		for (claim, metadata) in claims {
+			self.can_create_claim(caller, claim, metadata)?;
+			if !batch.insert(T::hash(claim)) {
+				return Err(DispatchError::ClaimAlreadyExists);
+			}
+			if self.claim_count(caller) + batch.len() > T::max_claims() as usize {
+				return Err(DispatchError::TooManyClaims);
+			}
+		}
+		Ok(())
+	}
+
+	/// Check whether the `caller` could revoke the `claim`, without changing any state. Both the
+	/// owner and the revoker authorized by the owner can revoke a claim.
+	pub fn can_revoke_claim(&self, caller: &T::AccountId, claim: &T::Content) -> DispatchResult {
+		match self.ensure_owner(caller, claim) {
+			Err(DispatchError::NotOwner) if self.revoker(claim) == Some(caller) => Ok(()),
+			result => result,
+		}
+	}
+
+	/// Check whether the `caller` could transfer the `claim`, without changing any state.
+	pub fn can_transfer_claim(
+		&self,
+		caller: &T::AccountId,
+		claim: &T::Content,
+		new_owner: &T::AccountId,
+	) -> DispatchResult {
+		self.ensure_owner(caller, claim)?;
+		// A claim the new owner already holds does not count twice.
+		if caller != new_owner && self.claim_count(new_owner) >= T::max_claims() as usize {
+			return Err(DispatchError::TooManyClaims);
+		}
+		Ok(())
+	}
+
+	/// Check whether the `caller` could authorize a revoker for the `claim`, without changing any
+	/// state.
+	pub fn can_set_revoker(
+		&self,
+		caller: &T::AccountId,
+		claim: &T::Content,
+		_delegate: &T::AccountId,
+	) -> DispatchResult {
+		self.ensure_owner(caller, claim)
+	}
+
+	/// Create each of the `claims` on behalf of the `caller`, in order, like `create_claim` at the
+	/// current block.
+	/// This stops at the first claim which fails, and returns its index in the batch and its
+	/// error. Note that this is not atomic: the claims created before the failure are kept.
+	pub fn try_create_claims(
+		&mut self,
+		caller: T::AccountId,
+		claims: Vec<(T::RawContent, T::Metadata)>,
+	) -> Result<(), (usize, DispatchError)> {
+		for (index, (claim, metadata)) in claims.into_iter().enumerate() {
+			self.create_claim(caller.clone(), claim, metadata).map_err(|e| (index, e))?;
+		}
+		Ok(())
+	}
+
+	/// Get the events emitted by this module which have not yet been collected by the runtime.
+	#[cfg(test)]
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	// Emit an event, to be collected by the runtime into the system event buffer.
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+
+	// Remove the `claim`, and everything stored about it, emitting an event for its owner.
+	fn remove_claim(&mut self, claim: &T::Content) {
+		let Some((owner, _)) = self.claims.remove(claim) else { return };
+		self.claim_block.remove(claim);
+		self.revocation_delegates.remove(claim);
+		self.expiry.remove(claim);
+		self.deposit_event(Event::ClaimRevoked { owner, content: claim.clone() });
+	}
+
+	// Ensure that the `claim` exists, and is owned by `who`.
+	fn ensure_owner(&self, who: &T::AccountId, claim: &T::Content) -> DispatchResult {
+		let owner = self.get_claim(claim).ok_or(DispatchError::ClaimNotFound)?;
+		if owner != who {
+			return Err(DispatchError::NotOwner);
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {
+	// The claims which expire at block `n` are revoked at its start, so their content can be
+	// claimed again.
+	fn on_initialize(&mut self, n: T::BlockNumber) {
+		self.block_number = n;
+
+		let expired = self
+			.expiry
+			.iter()
+			.filter(|(_, expiry)| **expiry <= n)
+			.map(|(claim, _)| claim.clone())
+			.collect::<Vec<_>>();
+		for claim in expired {
+			self.remove_claim(&claim);
+		}
+	}
+}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {
+	// The claims of an account which is killed are revoked, so their content can be claimed again.
+	fn on_killed_account(&mut self, who: &T::AccountId) {
+		let claims = self.claims_of(who).into_iter().cloned().collect::<Vec<_>>();
+		for claim in claims {
+			self.remove_claim(&claim);
+		}
+	}
+}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	const STORAGE_VERSION: u16 = 1;
+
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+
+	fn migrate(&mut self, from: u16, to: u16) {
+		// Version 1 stores the claims under `Config::migrate_content` of their previous content.
+		// When several claims get the same content, only the first of them is kept, and the others
+		// are revoked.
+		if from < 1 && to >= 1 {
+			for (content, (owner, _)) in self.claims.rekey(T::migrate_content) {
+				self.claim_block.remove(&content);
+				self.revocation_delegates.remove(&content);
+				self.expiry.remove(&content);
+				self.deposit_event(Event::ClaimRevoked { owner, content });
+			}
+			self.claim_block.rekey(T::migrate_content);
+			self.revocation_delegates.rekey(T::migrate_content);
+			self.expiry.rekey(T::migrate_content);
+		}
+		self.set_storage_version(to);
+	}
+}
+
+impl<T: Config, E: From<Event<T>>> TakeEvents<E> for Pallet<T> {
+	fn take_events(&mut self) -> Vec<E> {
+		self.events.drain(..).map(Into::into).collect()
 	}
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
-	/// Create a new claim on behalf of the `caller`.
-	/// This function will return an error if someone already has claimed that content.
-	pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-		if let Some(_) = self.get_claim(&claim) {
-			return Err("claim already exist");
-		};
-		self.claims.insert(claim, caller);
+	/// Create a new claim on behalf of the `caller`, with some `metadata` attached to it, recording
+	/// the current block as the block at which it was made. The raw content of the `claim` is
+	/// hashed, and only its hash is stored.
+	/// This function will return an error if someone already has claimed that content, or if the
+	/// caller already owns the maximum number of claims.
+	#[validate(can_create_claim)]
+	pub fn create_claim(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::RawContent,
+		metadata: T::Metadata,
+	) -> DispatchResult {
+		self.can_create_claim(&caller, &claim, &metadata)?;
+		let claim = T::hash(&claim);
+		self.claim_block.insert(claim.clone(), self.block_number);
+		self.claims.insert(claim.clone(), (caller.clone(), metadata));
+		self.deposit_event(Event::ClaimCreated { owner: caller, content: claim });
+		Ok(())
+	}
+
+	/// Create a new claim on behalf of the `caller`, like `create_claim`, which expires `ttl`
+	/// blocks after the current block. The claim is removed at the start of the block it expires
+	/// at, and from then on anyone can claim the content again.
+	/// This function will return an error like `create_claim`, or if the expiry block overflows.
+	#[validate(can_create_claim_with_expiry)]
+	pub fn create_claim_with_expiry(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::RawContent,
+		metadata: T::Metadata,
+		ttl: T::BlockNumber,
+	) -> DispatchResult {
+		self.can_create_claim_with_expiry(&caller, &claim, &metadata, &ttl)?;
+		let expiry =
+			self.block_number.checked_add(&ttl).ok_or(DispatchError::BlockNumberOverflow)?;
+		let content = T::hash(&claim);
+		self.create_claim(caller, claim, metadata)?;
+		self.expiry.insert(content, expiry);
 		Ok(())
 	}
 
+	/// Create each of the `claims` on behalf of the `caller`, in order, like `try_create_claims`.
+	/// Note that this is not atomic: the claims created before the first failure are kept.
+	#[weight(10)]
+	pub fn create_claims(
+		&mut self,
+		caller: T::AccountId,
+		claims: Vec<(T::RawContent, T::Metadata)>,
+	) -> DispatchResult {
+		self.try_create_claims(caller, claims).map_err(|(_, e)| e)
+	}
+
+	/// Create each of the `claims` on behalf of the `caller` atomically.
+	/// Every claim is first validated, taking the earlier claims of the batch into account, so
+	/// either all of the claims are created, or none of them are and the error of the first
+	/// failing claim is returned.
+	#[weight(10)]
+	#[validate(can_create_claims)]
+	pub fn create_claims_atomic(
+		&mut self,
+		caller: T::AccountId,
+		claims: Vec<(T::RawContent, T::Metadata)>,
+	) -> DispatchResult {
+		self.can_create_claims(&caller, &claims)?;
+		self.try_create_claims(caller, claims).map_err(|(_, e)| e)
+	}
+
 	/// Revoke an existing claim on some content.
+	/// This function should only succeed if the caller is the owner of an existing claim, or the
+	/// revoker the owner has authorized for it.
+	/// It will return an error if the claim does not exist, or if the caller is neither.
+	#[validate(can_revoke_claim)]
+	pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
+		self.can_revoke_claim(&caller, &claim)?;
+		self.remove_claim(&claim);
+		Ok(())
+	}
+
+	/// Authorize a `delegate` to revoke an existing claim on behalf of its owner, replacing any
+	/// revoker authorized before.
 	/// This function should only succeed if the caller is the owner of an existing claim.
 	/// It will return an error if the claim does not exist, or if the caller is not the owner.
-	pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-		let owner = self.get_claim(&claim).ok_or("claim does not exist")?;
-		if *owner != caller {
-			return Err("Caller is not the owner");
+	#[validate(can_set_revoker)]
+	pub fn set_revoker(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::Content,
+		delegate: T::AccountId,
+	) -> DispatchResult {
+		self.can_set_revoker(&caller, &claim, &delegate)?;
+		self.revocation_delegates.insert(claim, delegate);
+		Ok(())
+	}
+
+	/// Transfer an existing claim on some content to a `new_owner`, keeping its metadata. The
+	/// revoker authorized by the previous owner, if any, can no longer revoke it.
+	/// This function should only succeed if the caller is the owner of an existing claim.
+	/// It will return an error if the claim does not exist, if the caller is not the owner, or if
+	/// the new owner already holds the maximum number of claims.
+	#[validate(can_transfer_claim)]
+	pub fn transfer_claim(
+		&mut self,
+		caller: T::AccountId,
+		claim: T::Content,
+		new_owner: T::AccountId,
+	) -> DispatchResult {
+		self.can_transfer_claim(&caller, &claim, &new_owner)?;
+		if let Some((owner, _)) = self.claims.get_mut(&claim) {
+			*owner = new_owner;
 		}
-		self.claims.remove(&claim);
+		self.revocation_delegates.remove(&claim);
 		Ok(())
 	}
 }
 
 #[cfg(test)]
 mod test {
+	use core::hash::{Hash, Hasher};
+	use std::collections::hash_map::DefaultHasher;
+
+	use crate::support::{Dispatch, DispatchError, Origin};
+
+	#[derive(Debug, PartialEq, Eq)]
 	struct TestConfig;
 
+	// The content is stored as it is, to keep the tests readable.
 	impl super::Config for TestConfig {
+		type RawContent = &'static str;
 		type Content = &'static str;
+		type Metadata = &'static str;
+
+		fn max_claims() -> u32 {
+			2
+		}
+
+		fn hash(content: &Self::RawContent) -> Self::Content {
+			content
+		}
+	}
+
+	// A configuration which stores the hash of the content.
+	#[derive(Debug, PartialEq, Eq)]
+	struct HashConfig;
+
+	impl super::Config for HashConfig {
+		type RawContent = Vec<u8>;
+		type Content = u64;
+		type Metadata = ();
+
+		fn max_claims() -> u32 {
+			2
+		}
+
+		fn hash(content: &Self::RawContent) -> Self::Content {
+			let mut hasher = DefaultHasher::new();
+			content.hash(&mut hasher);
+			hasher.finish()
+		}
+	}
+
+	impl crate::system::Config for HashConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	// A configuration whose claims are stored under their upper case content from storage version
+	// 1.
+	#[derive(Debug, PartialEq, Eq)]
+	struct MigrationConfig;
+
+	impl super::Config for MigrationConfig {
+		type RawContent = &'static str;
+		type Content = String;
+		type Metadata = &'static str;
+
+		fn max_claims() -> u32 {
+			2
+		}
+
+		fn hash(content: &Self::RawContent) -> Self::Content {
+			content.to_string()
+		}
+
+		fn migrate_content(content: &Self::Content) -> Self::Content {
+			content.to_uppercase()
+		}
+	}
+
+	impl crate::system::Config for MigrationConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
 	}
 
 	impl crate::system::Config for TestConfig {
 		type AccountId = &'static str;
 		type BlockNumber = u32;
 		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
 	}
 
 	#[test]
@@ -75,23 +559,431 @@ mod test {
 		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
 		assert!(proof_of_existence.get_claim(&"data").is_none());
 
-		assert!(proof_of_existence.create_claim("alice", "data").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
 		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"alice"));
 		assert!(proof_of_existence.revoke_claim("alice", "data").is_ok());
 
-		assert!(proof_of_existence.create_claim("bob", "document").is_ok());
+		assert!(proof_of_existence.create_claim("bob", "document", "").is_ok());
 		assert_eq!(
-			proof_of_existence.create_claim("charlie", "document"),
-			Err("claim already exist")
+			proof_of_existence.create_claim("charlie", "document", ""),
+			Err(DispatchError::ClaimAlreadyExists)
 		);
 
 		assert_eq!(
 			proof_of_existence.revoke_claim("charlie", "document"),
-			Err("Caller is not the owner")
+			Err(DispatchError::NotOwner)
 		);
 		assert_eq!(
 			proof_of_existence.revoke_claim("charlie", "record"),
-			Err("claim does not exist")
+			Err(DispatchError::ClaimNotFound)
+		);
+	}
+
+	#[test]
+	fn claim_owner() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert_eq!(proof_of_existence.claim_owner(&"data"), None);
+
+		proof_of_existence.create_claim("alice", "data", "").unwrap();
+		let owner = proof_of_existence.claim_owner(&"data").unwrap();
+		assert_eq!(Some(&owner), proof_of_existence.get_claim(&"data"));
+		// The owner is not borrowed from the pallet, so the pallet can be changed on their behalf.
+		assert_eq!(proof_of_existence.revoke_claim(owner, "data"), Ok(()));
+		assert_eq!(proof_of_existence.claim_owner(&"data"), None);
+	}
+
+	#[test]
+	fn transfer_claim() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(
+			proof_of_existence.transfer_claim("alice", "data", "bob"),
+			Err(DispatchError::ClaimNotFound)
+		);
+
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		assert_eq!(
+			proof_of_existence.transfer_claim("bob", "data", "bob"),
+			Err(DispatchError::NotOwner)
+		);
+
+		assert!(proof_of_existence.transfer_claim("alice", "data", "bob").is_ok());
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"bob"));
+
+		// The previous owner no longer controls the claim, but the new owner does.
+		assert_eq!(proof_of_existence.revoke_claim("alice", "data"), Err(DispatchError::NotOwner));
+		assert!(proof_of_existence.revoke_claim("bob", "data").is_ok());
+		assert!(proof_of_existence.get_claim(&"data").is_none());
+
+		// A claim cannot be transferred to an account which holds the maximum number of claims.
+		assert!(proof_of_existence.create_claim("bob", "one", "").is_ok());
+		assert!(proof_of_existence.create_claim("bob", "two", "").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "three", "").is_ok());
+		assert_eq!(
+			proof_of_existence.transfer_claim("alice", "three", "bob"),
+			Err(DispatchError::TooManyClaims)
+		);
+		assert_eq!(proof_of_existence.get_claim(&"three"), Some(&"alice"));
+		assert!(proof_of_existence.transfer_claim("bob", "one", "bob").is_ok());
+	}
+
+	#[test]
+	fn revocation_delegate() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		// Only the owner can authorize a revoker.
+		assert_eq!(
+			proof_of_existence.set_revoker("bob", "data", "bob"),
+			Err(DispatchError::NotOwner)
+		);
+		assert!(proof_of_existence.set_revoker("alice", "data", "bob").is_ok());
+		assert_eq!(proof_of_existence.revoker(&"data"), Some(&"bob"));
+
+		// The revoker cannot transfer the claim, and no one else can revoke it.
+		assert_eq!(
+			proof_of_existence.transfer_claim("bob", "data", "bob"),
+			Err(DispatchError::NotOwner)
+		);
+		assert_eq!(
+			proof_of_existence.revoke_claim("charlie", "data"),
+			Err(DispatchError::NotOwner)
+		);
+
+		assert!(proof_of_existence.revoke_claim("bob", "data").is_ok());
+		assert!(proof_of_existence.get_claim(&"data").is_none());
+		assert!(proof_of_existence.revoker(&"data").is_none());
+		assert_eq!(
+			proof_of_existence.events().last(),
+			Some(&super::Event::ClaimRevoked { owner: "alice", content: "data" })
+		);
+	}
+
+	#[test]
+	fn killed_account() {
+		use crate::support::OnKilledAccount;
+
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		proof_of_existence.create_claim("alice", "data", "").unwrap();
+		proof_of_existence.create_claim("alice", "document", "").unwrap();
+		proof_of_existence.create_claim("bob", "record", "").unwrap();
+
+		proof_of_existence.on_killed_account(&"alice");
+		assert_eq!(proof_of_existence.claim_count(&"alice"), 0);
+		assert!(proof_of_existence.claim_block(&"data").is_none());
+		assert_eq!(proof_of_existence.get_claim(&"record"), Some(&"bob"));
+		assert_eq!(
+			proof_of_existence.events()[3..],
+			[
+				super::Event::ClaimRevoked { owner: "alice", content: "data" },
+				super::Event::ClaimRevoked { owner: "alice", content: "document" },
+			]
+		);
+	}
+
+	#[test]
+	fn claim_events() {
+		use super::Event;
+		use crate::support::TakeEvents;
+
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		// Failed calls do not emit an event.
+		assert!(proof_of_existence.create_claim("bob", "data", "").is_err());
+		assert!(proof_of_existence.revoke_claim("bob", "data").is_err());
+		assert!(proof_of_existence.revoke_claim("alice", "data").is_ok());
+
+		let events: Vec<Event<TestConfig>> = proof_of_existence.take_events();
+		assert_eq!(
+			events,
+			vec![
+				Event::ClaimCreated { owner: "alice", content: "data" },
+				Event::ClaimRevoked { owner: "alice", content: "data" },
+			]
+		);
+		assert!(proof_of_existence.events().is_empty());
+	}
+
+	#[test]
+	fn claim_creation_block() {
+		use crate::support::Hooks;
+
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert!(proof_of_existence.claim_block(&"data").is_none());
+
+		// The claim is recorded at the current block, which the caller cannot choose.
+		proof_of_existence.on_initialize(5);
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		assert_eq!(proof_of_existence.claim_block(&"data"), Some(5));
+
+		// A failed claim does not overwrite the creation block.
+		assert!(proof_of_existence.create_claim("bob", "data", "").is_err());
+		assert_eq!(proof_of_existence.claim_block(&"data"), Some(5));
+
+		assert!(proof_of_existence.revoke_claim("alice", "data").is_ok());
+		assert!(proof_of_existence.claim_block(&"data").is_none());
+	}
+
+	#[test]
+	fn count_claims_of_account() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert_eq!(proof_of_existence.claim_count(&"alice"), 0);
+
+		assert!(proof_of_existence.create_claim("alice", "record", "").is_ok());
+		assert!(proof_of_existence.create_claim("bob", "document", "").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+
+		assert_eq!(proof_of_existence.claim_count(&"alice"), 2);
+		assert_eq!(proof_of_existence.claim_count(&"bob"), 1);
+		assert_eq!(proof_of_existence.claim_count(&"charlie"), 0);
+
+		assert_eq!(proof_of_existence.claims_of(&"alice"), vec![&"data", &"record"]);
+		assert_eq!(proof_of_existence.claims_of(&"bob"), vec![&"document"]);
+		assert!(proof_of_existence.claims_of(&"charlie").is_empty());
+	}
+
+	#[test]
+	fn max_claims_per_account() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "document", "").is_ok());
+		assert_eq!(
+			proof_of_existence.create_claim("alice", "record", ""),
+			Err(DispatchError::TooManyClaims)
+		);
+		assert!(proof_of_existence.get_claim(&"record").is_none());
+
+		// Other accounts have their own limit.
+		assert!(proof_of_existence.create_claim("bob", "record", "").is_ok());
+
+		// Revoking a claim frees up a slot again.
+		assert!(proof_of_existence.revoke_claim("alice", "data").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "note", "").is_ok());
+	}
+
+	#[test]
+	fn claim_metadata() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert!(proof_of_existence.claim_metadata(&"data").is_none());
+
+		assert!(proof_of_existence.create_claim("alice", "data", "signed contract").is_ok());
+		assert_eq!(proof_of_existence.claim_metadata(&"data"), Some(&"signed contract"));
+
+		// The metadata stays with the claim when it changes owner.
+		assert!(proof_of_existence.transfer_claim("alice", "data", "bob").is_ok());
+		assert_eq!(proof_of_existence.claim_metadata(&"data"), Some(&"signed contract"));
+
+		assert!(proof_of_existence.revoke_claim("bob", "data").is_ok());
+		assert!(proof_of_existence.claim_metadata(&"data").is_none());
+	}
+
+	#[test]
+	fn iterate_claims() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert_eq!(proof_of_existence.total_claims(), 0);
+
+		assert!(proof_of_existence.create_claim("bob", "record", "").is_ok());
+		assert!(proof_of_existence.create_claim("charlie", "data", "").is_ok());
+		assert!(proof_of_existence.create_claim("alice", "document", "").is_ok());
+
+		assert_eq!(
+			proof_of_existence.iter_claims().collect::<Vec<_>>(),
+			vec![(&"data", &"charlie"), (&"document", &"alice"), (&"record", &"bob")]
+		);
+		assert_eq!(proof_of_existence.total_claims(), 3);
+	}
+
+	#[test]
+	fn contains_claim() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		assert!(!proof_of_existence.contains_claim(&"data"));
+
+		assert!(proof_of_existence.create_claim("alice", "data", "").is_ok());
+		assert!(proof_of_existence.contains_claim(&"data"));
+		assert!(!proof_of_existence.contains_claim(&"document"));
+	}
+
+	#[test]
+	fn can_dispatch() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		let revoke = super::Call::revoke_claim { claim: "data" };
+		assert_eq!(
+			proof_of_existence.can_dispatch(&Origin::Signed("alice"), &revoke),
+			Err(DispatchError::ClaimNotFound)
+		);
+
+		proof_of_existence.create_claim("alice", "data", "").unwrap();
+
+		let create = super::Call::create_claim { claim: "data", metadata: "" };
+		assert_eq!(
+			proof_of_existence.can_dispatch(&Origin::Signed("bob"), &create),
+			Err(DispatchError::ClaimAlreadyExists)
+		);
+		let transfer = super::Call::transfer_claim { claim: "data", new_owner: "bob" };
+		assert_eq!(
+			proof_of_existence.can_dispatch(&Origin::Signed("bob"), &transfer),
+			Err(DispatchError::NotOwner)
+		);
+		assert_eq!(proof_of_existence.can_dispatch(&Origin::Signed("alice"), &transfer), Ok(()));
+		assert_eq!(proof_of_existence.can_dispatch(&Origin::Signed("alice"), &revoke), Ok(()));
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"alice"));
+	}
+
+	#[test]
+	fn hashed_claims() {
+		let mut proof_of_existence = super::Pallet::<HashConfig>::new();
+		let document = b"a large document".to_vec();
+		let other = b"another large document".to_vec();
+		let hash = <HashConfig as super::Config>::hash;
+
+		assert_eq!(proof_of_existence.create_claim("alice", document.clone(), ()), Ok(()));
+		assert_eq!(proof_of_existence.create_claim("bob", other.clone(), ()), Ok(()));
+		assert_ne!(hash(&document), hash(&other));
+		assert_eq!(proof_of_existence.get_claim(&hash(&document)), Some(&"alice"));
+		assert_eq!(proof_of_existence.get_claim(&hash(&other)), Some(&"bob"));
+
+		// The same payload hashes to the same claim.
+		assert_eq!(
+			proof_of_existence.create_claim("bob", document, ()),
+			Err(DispatchError::ClaimAlreadyExists)
+		);
+	}
+
+	#[test]
+	fn migrate_claims() {
+		use crate::support::{Hooks, Migrate};
+
+		let mut proof_of_existence = super::Pallet::<MigrationConfig>::new();
+		assert_eq!(proof_of_existence.storage_version(), 1);
+		proof_of_existence.on_initialize(3);
+
+		// A claim which was stored before version 1.
+		proof_of_existence.set_storage_version(0);
+		proof_of_existence.create_claim("alice", "data", "note").unwrap();
+		proof_of_existence.set_revoker("alice", String::from("data"), "bob").unwrap();
+
+		proof_of_existence.migrate(0, 1);
+		assert_eq!(proof_of_existence.storage_version(), 1);
+		assert!(proof_of_existence.get_claim(&String::from("data")).is_none());
+		let claim = String::from("DATA");
+		assert_eq!(proof_of_existence.get_claim(&claim), Some(&"alice"));
+		assert_eq!(proof_of_existence.claim_metadata(&claim), Some(&"note"));
+		assert_eq!(proof_of_existence.claim_block(&claim), Some(3));
+		assert_eq!(proof_of_existence.revoker(&claim), Some(&"bob"));
+
+		// Two claims which get the same content: the first one is kept, and the other is revoked.
+		proof_of_existence.set_storage_version(0);
+		proof_of_existence.create_claim("bob", "Data", "").unwrap();
+		proof_of_existence.set_revoker("bob", String::from("Data"), "charlie").unwrap();
+		proof_of_existence.migrate(0, 1);
+		assert_eq!(proof_of_existence.get_claim(&claim), Some(&"alice"));
+		assert_eq!(proof_of_existence.revoker(&claim), Some(&"bob"));
+		assert!(proof_of_existence.get_claim(&String::from("Data")).is_none());
+		assert_eq!(
+			proof_of_existence.events().last(),
+			Some(&super::Event::ClaimRevoked { owner: "bob", content: String::from("Data") })
+		);
+	}
+
+	#[test]
+	fn claim_expiry() {
+		use crate::support::Hooks;
+
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+
+		proof_of_existence.on_initialize(1);
+		assert!(proof_of_existence.create_claim_with_expiry("alice", "data", "", 2).is_ok());
+		assert_eq!(proof_of_existence.claim_block(&"data"), Some(1));
+		assert_eq!(proof_of_existence.claim_expiry(&"data"), Some(3));
+
+		proof_of_existence.on_initialize(2);
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"alice"));
+		assert_eq!(
+			proof_of_existence.create_claim("bob", "data", ""),
+			Err(DispatchError::ClaimAlreadyExists)
+		);
+
+		// The claim is gone once its expiry block starts, so anyone can claim the content again.
+		proof_of_existence.on_initialize(3);
+		assert!(proof_of_existence.get_claim(&"data").is_none());
+		assert!(proof_of_existence.claim_expiry(&"data").is_none());
+		assert_eq!(
+			proof_of_existence.events().last(),
+			Some(&super::Event::ClaimRevoked { owner: "alice", content: "data" })
+		);
+		assert!(proof_of_existence.create_claim("bob", "data", "").is_ok());
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"bob"));
+
+		// A claim made without an expiry stays.
+		proof_of_existence.on_initialize(100);
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"bob"));
+	}
+
+	#[test]
+	fn create_claims() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		proof_of_existence.create_claim("bob", "record", "").unwrap();
+
+		// The claim at index 2 conflicts with the claim of bob, so the claims before it are kept,
+		// and the claim after it is never made.
+		assert_eq!(
+			proof_of_existence.try_create_claims(
+				"alice",
+				vec![("data", ""), ("document", ""), ("record", ""), ("file", "")]
+			),
+			Err((2, DispatchError::ClaimAlreadyExists))
+		);
+		assert_eq!(proof_of_existence.get_claim(&"data"), Some(&"alice"));
+		assert_eq!(proof_of_existence.get_claim(&"document"), Some(&"alice"));
+		assert_eq!(proof_of_existence.get_claim(&"record"), Some(&"bob"));
+		assert!(!proof_of_existence.contains_claim(&"file"));
+
+		// Bob already owns a claim, so only one more fits.
+		let call = super::Call::create_claims { claims: vec![("file", "a"), ("image", "b")] };
+		assert_eq!(
+			proof_of_existence.dispatch(Origin::Signed("bob"), call),
+			Err(DispatchError::TooManyClaims)
+		);
+		assert_eq!(proof_of_existence.get_claim(&"file"), Some(&"bob"));
+		assert_eq!(proof_of_existence.claim_metadata(&"file"), Some(&"a"));
+	}
+
+	#[test]
+	fn create_claims_atomic() {
+		let mut proof_of_existence = super::Pallet::<TestConfig>::new();
+		proof_of_existence.create_claim("bob", "record", "").unwrap();
+
+		assert_eq!(
+			proof_of_existence.create_claims_atomic("alice", vec![("data", ""), ("record", "")]),
+			Err(DispatchError::ClaimAlreadyExists)
+		);
+		assert!(!proof_of_existence.contains_claim(&"data"));
+
+		// The claims made earlier in the batch are taken into account.
+		assert_eq!(
+			proof_of_existence.create_claims_atomic("alice", vec![("data", ""), ("data", "")]),
+			Err(DispatchError::ClaimAlreadyExists)
+		);
+		let call = super::Call::create_claims_atomic {
+			claims: vec![("data", ""), ("document", ""), ("file", "")],
+		};
+		assert_eq!(
+			proof_of_existence.can_dispatch(&Origin::Signed("alice"), &call),
+			Err(DispatchError::TooManyClaims)
+		);
+		assert_eq!(
+			proof_of_existence.dispatch(Origin::Signed("alice"), call),
+			Err(DispatchError::TooManyClaims)
+		);
+		assert_eq!(proof_of_existence.claim_count(&"alice"), 0);
+
+		assert_eq!(
+			proof_of_existence.create_claims_atomic("alice", vec![("data", ""), ("document", "")]),
+			Ok(())
 		);
+		assert_eq!(proof_of_existence.claims_of(&"alice"), vec![&"data", &"document"]);
 	}
 }
@@ -0,0 +1,288 @@
+use std::io::{BufRead, Write};
+
+use crate::{balances, proof_of_existence, support, types, Runtime, RuntimeCall};
+
+/// The commands which can be typed into the REPL, one per line.
+const USAGE: &str = "usage:
+	transfer <from> <to> <amount>
+	claim <owner> <content>
+	balance <who>
+	account <who>
+	faucet <who>
+	block";
+
+/// A command typed into the REPL.
+#[derive(Debug, Clone)]
+pub enum Command {
+	/// Apply the `call` on behalf of the `caller`, on top of the current block.
+	Call { caller: types::AccountId, call: RuntimeCall },
+	/// Print the balance of the account.
+	Balance(types::AccountId),
+	/// Print the composition of the balance of the account, and what keeps it alive.
+	Account(types::AccountId),
+	/// Drip the faucet into the account, at the current block.
+	Faucet(types::AccountId),
+	/// Execute the next block.
+	Block,
+}
+
+/// The reasons a line cannot be parsed into a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+	/// The line has no command.
+	Empty,
+	/// The command is not one of the known commands.
+	UnknownCommand(String),
+	/// The command is given the wrong number of arguments.
+	WrongArguments(&'static str),
+	/// The amount is not a valid balance.
+	InvalidAmount(String),
+	/// The account is neither the name of a development account, nor a valid address.
+	InvalidAccount(String),
+}
+
+impl core::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Empty => write!(f, "no command given"),
+			Self::UnknownCommand(command) => write!(f, "unknown command {:?}", command),
+			Self::WrongArguments(command) => write!(f, "wrong arguments for {:?}", command),
+			Self::InvalidAmount(amount) => write!(f, "invalid amount {:?}", amount),
+			Self::InvalidAccount(account) => write!(f, "invalid account {:?}", account),
+		}
+	}
+}
+
+/// Parse a `line` into a command.
+///
+/// Accounts are given either by the name of a development account, like `alice`, or by their
+/// address.
+pub fn parse(line: &str) -> Result<Command, ParseError> {
+	let words = line.split_whitespace().collect::<Vec<_>>();
+	let Some((&command, args)) = words.split_first() else { return Err(ParseError::Empty) };
+	match (command, args) {
+		("transfer", &[from, to, amount]) => {
+			let amount = amount.parse().map_err(|_| ParseError::InvalidAmount(amount.into()))?;
+			let call = balances::Call::transfer { to: account(to)?, amount };
+			Ok(Command::Call { caller: account(from)?, call: RuntimeCall::balances(call) })
+		},
+		("claim", &[owner, content]) => {
+			let call = proof_of_existence::Call::create_claim {
+				claim: content.into(),
+				metadata: String::new(),
+			};
+			Ok(Command::Call {
+				caller: account(owner)?,
+				call: RuntimeCall::proof_of_existence(call),
+			})
+		},
+		("balance", &[who]) => Ok(Command::Balance(account(who)?)),
+		("account", &[who]) => Ok(Command::Account(account(who)?)),
+		("faucet", &[who]) => Ok(Command::Faucet(account(who)?)),
+		("block", &[]) => Ok(Command::Block),
+		("transfer", _) => Err(ParseError::WrongArguments("transfer")),
+		("claim", _) => Err(ParseError::WrongArguments("claim")),
+		("balance", _) => Err(ParseError::WrongArguments("balance")),
+		("account", _) => Err(ParseError::WrongArguments("account")),
+		("faucet", _) => Err(ParseError::WrongArguments("faucet")),
+		("block", _) => Err(ParseError::WrongArguments("block")),
+		_ => Err(ParseError::UnknownCommand(command.into())),
+	}
+}
+
+// Parse an account, given by its address or by the name of a development account.
+fn account(s: &str) -> Result<types::AccountId, ParseError> {
+	if s.starts_with("0x") {
+		s.parse().map_err(|_| ParseError::InvalidAccount(s.into()))
+	} else if s.len() <= support::AccountId::LEN {
+		Ok(support::AccountId::from_name(s))
+	} else {
+		Err(ParseError::InvalidAccount(s.into()))
+	}
+}
+
+/// Read commands from the `input`, one per line, and run them against the `runtime`, writing
+/// their results to the `output`, until the input ends.
+///
+/// Calls are applied as extrinsics on top of the current block, with the next nonce of their
+/// caller. Lines which cannot be parsed print a usage hint.
+pub fn run(
+	runtime: &mut Runtime,
+	input: impl BufRead,
+	mut output: impl Write,
+) -> std::io::Result<()> {
+	for line in input.lines() {
+		let line = line?;
+		match parse(&line) {
+			Ok(Command::Call { caller, call }) => {
+				let nonce = runtime.system.next_nonce(&caller);
+				let extrinsic = support::Extrinsic { caller, nonce, call };
+				match runtime.apply_extrinsic(extrinsic) {
+					Ok(()) => writeln!(output, "ok")?,
+					Err(e) => writeln!(output, "error: {}", e)?,
+				}
+			},
+			Ok(Command::Balance(who)) =>
+				writeln!(output, "{}", runtime.balances.format_balance(&who))?,
+			Ok(Command::Account(who)) => {
+				let balances::AccountData { free, reserved, frozen } =
+					runtime.balances.account_data(&who);
+				let (providers, consumers) =
+					(runtime.system.providers(&who), runtime.system.consumers(&who));
+				writeln!(
+					output,
+					"free {}, reserved {}, frozen {}, providers {}, consumers {}",
+					free, reserved, frozen, providers, consumers
+				)?
+			},
+			Ok(Command::Faucet(who)) => {
+				let now = runtime.system.block_number();
+				let result = runtime.balances.faucet(&who, now);
+				// The drip is not part of a block, so the account it may create is passed on to
+				// the system pallet right away.
+				runtime.collect_events();
+				match result {
+					Ok(()) => writeln!(output, "ok")?,
+					Err(e) => writeln!(output, "error: {}", e)?,
+				}
+			},
+			Ok(Command::Block) => {
+				let block_number = runtime.system.block_number().saturating_add(1);
+				let block =
+					types::Block { header: support::Header { block_number }, extrinsics: vec![] };
+				match runtime.execute_block(block) {
+					Ok(()) => writeln!(output, "block {}", block_number)?,
+					Err(e) => writeln!(output, "error: {}", e)?,
+				}
+			},
+			Err(ParseError::Empty) => {},
+			Err(e) => writeln!(output, "{}\n{}", e, USAGE)?,
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{parse, Command, ParseError};
+	use crate::{balances, proof_of_existence, types::AccountId, RuntimeCall};
+
+	#[test]
+	fn parse_transfer() {
+		let Ok(Command::Call { caller, call }) = parse("transfer alice bob 30") else {
+			panic!("expected a call")
+		};
+		assert_eq!(caller, AccountId::from_name("alice"));
+		assert!(matches!(
+			call,
+			RuntimeCall::balances(balances::Call::transfer { to, amount: 30 })
+				if to == AccountId::from_name("bob")
+		));
+
+		// Accounts can also be given by their address.
+		let bob = AccountId::from_name("bob");
+		let line = format!("  transfer   alice {}  30 ", bob);
+		assert!(matches!(
+			parse(&line),
+			Ok(Command::Call {
+				call: RuntimeCall::balances(balances::Call::transfer { to, .. }),
+				..
+			}) if to == bob
+		));
+	}
+
+	#[test]
+	fn parse_claim() {
+		let Ok(Command::Call { caller, call }) = parse("claim alice data") else {
+			panic!("expected a call")
+		};
+		assert_eq!(caller, AccountId::from_name("alice"));
+		assert!(matches!(
+			call,
+			RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+				claim,
+				..
+			}) if claim == "data"
+		));
+	}
+
+	#[test]
+	fn parse_queries() {
+		assert!(matches!(
+			parse("balance alice"),
+			Ok(Command::Balance(who)) if who == AccountId::from_name("alice")
+		));
+		assert!(matches!(
+			parse("account alice"),
+			Ok(Command::Account(who)) if who == AccountId::from_name("alice")
+		));
+		assert!(matches!(
+			parse("faucet alice"),
+			Ok(Command::Faucet(who)) if who == AccountId::from_name("alice")
+		));
+		assert!(matches!(parse("block"), Ok(Command::Block)));
+	}
+
+	#[test]
+	fn parse_malformed() {
+		assert!(matches!(parse(""), Err(ParseError::Empty)));
+		assert!(matches!(parse("   "), Err(ParseError::Empty)));
+		assert!(matches!(
+			parse("mint alice 30"),
+			Err(ParseError::UnknownCommand(command)) if command == "mint"
+		));
+		assert!(matches!(parse("transfer alice bob"), Err(ParseError::WrongArguments("transfer"))));
+		assert!(matches!(parse("block 2"), Err(ParseError::WrongArguments("block"))));
+		assert!(matches!(
+			parse("transfer alice bob -30"),
+			Err(ParseError::InvalidAmount(amount)) if amount == "-30"
+		));
+		assert!(matches!(
+			parse("balance 0xalice"),
+			Err(ParseError::InvalidAccount(account)) if account == "0xalice"
+		));
+	}
+
+	#[test]
+	fn run() {
+		let mut runtime = crate::Runtime::new();
+		runtime.balances.mint(&AccountId::from_name("alice"), 100).unwrap();
+
+		let input = "transfer alice bob 30\nbalance bob\nmint bob 1\n\nblock\nbalance alice\n";
+		let mut output = Vec::new();
+		super::run(&mut runtime, input.as_bytes(), &mut output).unwrap();
+
+		let output = String::from_utf8(output).unwrap();
+		let mut lines = output.lines();
+		assert_eq!(lines.next(), Some("ok"));
+		assert_eq!(lines.next(), Some("30 UNIT"));
+		assert_eq!(lines.next(), Some("unknown command \"mint\""));
+		assert_eq!(lines.next(), Some("usage:"));
+		assert!(output.contains("block 1\n"));
+		// Alice paid a fee of 1 for the transfer.
+		assert!(output.ends_with("69 UNIT\n"));
+	}
+
+	#[test]
+	fn run_account() {
+		let mut runtime = crate::Runtime::new();
+
+		// A second drip is only given once the cooldown has passed.
+		let input = "faucet alice
+faucet alice
+account alice
+";
+		let mut output = Vec::new();
+		super::run(&mut runtime, input.as_bytes(), &mut output).unwrap();
+
+		let output = String::from_utf8(output).unwrap();
+		assert_eq!(
+			output.lines().collect::<Vec<_>>(),
+			vec![
+				"ok",
+				"error: faucet cooldown",
+				"free 100, reserved 0, frozen 0, providers 1, consumers 0",
+			]
+		);
+	}
+}
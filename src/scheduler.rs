@@ -0,0 +1,210 @@
+use num::Zero;
+
+use crate::support::{DispatchError, DispatchResult, Hooks, StorageMap, TakeEvents};
+
+pub trait Config: crate::system::Config {
+	/// The aggregated call type of the runtime, which is dispatched when a scheduled block is
+	/// reached.
+	type RuntimeCall;
+}
+
+/// A scheduled call, with the account on behalf of which it is dispatched.
+pub type Task<T> = (<T as crate::system::Config>::AccountId, <T as Config>::RuntimeCall);
+
+/// The events emitted by the scheduler module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<T: Config> {
+	/// The call at `index` in the agenda of block `when` was dispatched, with `result`.
+	Dispatched { when: T::BlockNumber, index: u32, result: DispatchResult },
+}
+
+/// This is the Scheduler Module.
+/// It keeps the calls which should be dispatched at a future block.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::BlockNumber: serde::Serialize, \
+			T::RuntimeCall: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, \
+			T::BlockNumber: serde::Deserialize<'de>, T::RuntimeCall: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A map from a block number to the calls to dispatch at that block, with the account which
+	/// scheduled each of them.
+	// The `Task` alias is not used here, since the derives would not see through it.
+	#[allow(clippy::type_complexity)]
+	agenda: StorageMap<T::BlockNumber, Vec<(T::AccountId, T::RuntimeCall)>>,
+	/// The current block number, which is noted at the start of each block.
+	block_number: T::BlockNumber,
+	/// The events emitted by this module which have not yet been collected by the runtime.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	events: Vec<Event<T>>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Scheduler Module.
+	pub fn new() -> Self {
+		Self {
+			agenda: StorageMap::new(),
+			block_number: T::BlockNumber::zero(),
+			events: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the calls scheduled for the block `when`.
+	#[cfg(test)]
+	pub fn agenda(&self, when: &T::BlockNumber) -> &[Task<T>] {
+		self.agenda.get(when).map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// Remove and return the calls scheduled for the block `when`, in the order they were
+	/// scheduled.
+	pub fn take_agenda(&mut self, when: &T::BlockNumber) -> Vec<Task<T>> {
+		self.agenda.remove(when).unwrap_or_default()
+	}
+
+	/// Note the `result` of dispatching the call at `index` in the agenda of block `when`.
+	pub fn note_dispatched(&mut self, when: T::BlockNumber, index: u32, result: DispatchResult) {
+		self.deposit_event(Event::Dispatched { when, index, result });
+	}
+
+	/// Get the events emitted by this module which have not yet been collected by the runtime.
+	#[cfg(test)]
+	pub fn events(&self) -> &[Event<T>] {
+		&self.events
+	}
+
+	// Buffer an event, until the runtime collects it.
+	fn deposit_event(&mut self, event: Event<T>) {
+		self.events.push(event);
+	}
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+	fn on_initialize(&mut self, n: T::BlockNumber) {
+		self.block_number = n;
+	}
+}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E: From<Event<T>>> TakeEvents<E> for Pallet<T> {
+	fn take_events(&mut self) -> Vec<E> {
+		self.events.drain(..).map(Into::into).collect()
+	}
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Schedule the `call` to be dispatched on behalf of the `caller` at the block `when`.
+	/// This function will return an error if `when` is not a future block.
+	// The call is boxed, since the `RuntimeCall` contains this `Call` and would otherwise have an
+	// infinite size.
+	#[allow(clippy::boxed_local)]
+	pub fn schedule(
+		&mut self,
+		caller: T::AccountId,
+		when: T::BlockNumber,
+		call: Box<T::RuntimeCall>,
+	) -> DispatchResult {
+		if when <= self.block_number {
+			return Err(DispatchError::ScheduleInPast);
+		}
+		match self.agenda.get_mut(&when) {
+			Some(calls) => calls.push((caller, *call)),
+			None => {
+				self.agenda.insert(when, vec![(caller, *call)]);
+			},
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::support::{DispatchError, Hooks};
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	impl super::Config for TestConfig {
+		type RuntimeCall = &'static str;
+	}
+
+	#[test]
+	fn schedule_calls() {
+		let mut scheduler = super::Pallet::<TestConfig>::new();
+		scheduler.on_initialize(1);
+
+		assert_eq!(scheduler.schedule("alice", 3, Box::new("first")), Ok(()));
+		assert_eq!(scheduler.schedule("bob", 3, Box::new("second")), Ok(()));
+		assert_eq!(scheduler.agenda(&3), &[("alice", "first"), ("bob", "second")]);
+		assert!(scheduler.agenda(&2).is_empty());
+
+		assert!(scheduler.take_agenda(&2).is_empty());
+		assert_eq!(scheduler.take_agenda(&3), vec![("alice", "first"), ("bob", "second")]);
+		assert!(scheduler.agenda(&3).is_empty());
+	}
+
+	#[test]
+	fn schedule_in_the_past() {
+		let mut scheduler = super::Pallet::<TestConfig>::new();
+		scheduler.on_initialize(2);
+
+		assert_eq!(
+			scheduler.schedule("alice", 2, Box::new("call")),
+			Err(DispatchError::ScheduleInPast)
+		);
+		assert_eq!(
+			scheduler.schedule("alice", 1, Box::new("call")),
+			Err(DispatchError::ScheduleInPast)
+		);
+		assert!(scheduler.agenda(&2).is_empty());
+	}
+
+	#[test]
+	fn dispatched_events() {
+		let mut scheduler = super::Pallet::<TestConfig>::new();
+
+		scheduler.note_dispatched(3, 0, Ok(()));
+		scheduler.note_dispatched(3, 1, Err(DispatchError::InsufficientBalance));
+		assert_eq!(
+			scheduler.events(),
+			&[
+				super::Event::Dispatched { when: 3, index: 0, result: Ok(()) },
+				super::Event::Dispatched {
+					when: 3,
+					index: 1,
+					result: Err(DispatchError::InsufficientBalance)
+				},
+			]
+		);
+	}
+}
@@ -1,3 +1,6 @@
+use core::{borrow::Borrow, fmt::Debug};
+use std::collections::{btree_map, BTreeMap};
+
 /// The most primitive representation of a Blockchain block.
 pub struct Block<Header, Extrinsic> {
 	/// The block header contains metadata about the block.
@@ -19,14 +22,419 @@ pub struct Header<BlockNumber> {
 /// This is an "extrinsic": literally an external message from outside of the blockchain.
 /// This simplified version of an extrinsic tells us who is making the call, and which call they are
 /// making.
-pub struct Extrinsic<Caller, Call> {
+/// The `nonce` protects against replaying the extrinsic: it must be the nonce the caller will have
+/// once this extrinsic is applied, which is their current nonce plus one.
+pub struct Extrinsic<Caller, Nonce, Call> {
 	pub caller: Caller,
+	pub nonce: Nonce,
 	pub call: Call,
 }
 
+/// The weight of a call: an abstract measure of the computation needed to execute it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weight(u64);
+
+impl Weight {
+	/// The largest possible weight.
+	pub const MAX: Self = Self(u64::MAX);
+
+	/// Create a weight of `units`.
+	pub const fn new(units: u64) -> Self {
+		Self(units)
+	}
+
+	/// Get the number of units of this weight.
+	pub const fn units(self) -> u64 {
+		self.0
+	}
+
+	/// Add two weights, saturating at `Weight::MAX` instead of overflowing.
+	pub const fn saturating_add(self, other: Self) -> Self {
+		Self(self.0.saturating_add(other.0))
+	}
+}
+
+/// A fraction in parts per billion, for example to take a proportion of an amount.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Perbill(u32);
+
+impl Perbill {
+	/// The number of parts which make up a whole.
+	const BILLION: u32 = 1_000_000_000;
+
+	/// Nothing.
+	pub const ZERO: Self = Self(0);
+
+	/// The whole, or 100%.
+	pub const ONE: Self = Self(Self::BILLION);
+
+	/// Create a fraction of `parts` per billion, saturating at the whole.
+	pub const fn from_parts(parts: u32) -> Self {
+		if parts > Self::BILLION {
+			Self::ONE
+		} else {
+			Self(parts)
+		}
+	}
+
+	/// Create a fraction of `percent` per hundred, saturating at the whole.
+	pub const fn from_percent(percent: u32) -> Self {
+		if percent > 100 {
+			Self::ONE
+		} else {
+			Self(percent * (Self::BILLION / 100))
+		}
+	}
+
+	/// Get the number of parts per billion of this fraction.
+	pub const fn parts(self) -> u32 {
+		self.0
+	}
+
+	/// Multiply `amount` by this fraction, rounding down.
+	pub fn mul_floor<N>(self, amount: N) -> N
+	where
+		N: Into<u128> + TryFrom<u128>,
+	{
+		let (amount, parts, billion) = (amount.into(), self.0 as u128, Self::BILLION as u128);
+		// Splitting the amount at a billion keeps the products from overflowing.
+		let result = amount / billion * parts + amount % billion * parts / billion;
+		// The result is at most the amount, so it always fits.
+		N::try_from(result).unwrap_or_else(|_| unreachable!("the result is at most the amount"))
+	}
+
+	/// Divide `amount` by this fraction, rounding down: get the amount which this fraction of is
+	/// `amount`. The result saturates at the largest value of `N`, for example when dividing by
+	/// zero.
+	pub fn saturating_reciprocal_mul<N>(self, amount: N) -> N
+	where
+		N: Into<u128> + TryFrom<u128> + num::Bounded,
+	{
+		let amount = amount.into();
+		let result = match amount {
+			0 => Some(0),
+			_ => amount
+				.checked_mul(Self::BILLION as u128)
+				.and_then(|amount| amount.checked_div(self.0 as u128)),
+		};
+		result.and_then(|result| N::try_from(result).ok()).unwrap_or_else(N::max_value)
+	}
+}
+
+/// A hasher which always gives the same hash for the same bytes, unlike the `DefaultHasher` of the
+/// standard library, whose algorithm may change between releases of Rust. This makes it fit for
+/// hashes which are stored in the state. It implements the 64-bit FNV-1a hash.
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl core::hash::Hasher for StableHasher {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+/// An account id of 32 bytes, like a public key.
+/// It is written as an address of `0x` followed by the hex encoding of its bytes.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AccountId([u8; 32]);
+
+impl AccountId {
+	/// The number of bytes of an account id.
+	pub const LEN: usize = 32;
+
+	/// Create an account id from its `bytes`.
+	pub const fn new(bytes: [u8; 32]) -> Self {
+		Self(bytes)
+	}
+
+	/// Create the account id of a development account from its `name`, like `alice`: the bytes of
+	/// the name, padded with zeros.
+	///
+	/// Panics if the name is longer than 32 bytes.
+	pub fn from_name(name: &str) -> Self {
+		let mut bytes = [0; 32];
+		bytes[..name.len()].copy_from_slice(name.as_bytes());
+		Self(bytes)
+	}
+
+	/// Get the bytes of this account id.
+	pub const fn as_bytes(&self) -> &[u8; 32] {
+		&self.0
+	}
+}
+
+impl From<[u8; 32]> for AccountId {
+	fn from(bytes: [u8; 32]) -> Self {
+		Self(bytes)
+	}
+}
+
+impl core::fmt::Display for AccountId {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str("0x")?;
+		self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+	}
+}
+
+impl Debug for AccountId {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Display::fmt(self, f)
+	}
+}
+
+/// The reasons an address cannot be parsed into an account id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountIdParseError {
+	/// The address does not start with `0x`.
+	MissingPrefix,
+	/// The address does not encode exactly 32 bytes. This is the number of hex digits it has.
+	InvalidLength(usize),
+	/// The address has a character which is not a hex digit.
+	InvalidCharacter(char),
+}
+
+impl core::fmt::Display for AccountIdParseError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::MissingPrefix => f.write_str("address must start with 0x"),
+			Self::InvalidLength(len) =>
+				write!(f, "address must have {} hex digits, found {}", AccountId::LEN * 2, len),
+			Self::InvalidCharacter(c) => write!(f, "invalid hex digit {:?} in address", c),
+		}
+	}
+}
+
+impl std::error::Error for AccountIdParseError {}
+
+impl core::str::FromStr for AccountId {
+	type Err = AccountIdParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hex = s.strip_prefix("0x").ok_or(AccountIdParseError::MissingPrefix)?;
+		if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+			return Err(AccountIdParseError::InvalidCharacter(c));
+		}
+		// Every character is a hex digit, so it is a single byte.
+		if hex.len() != Self::LEN * 2 {
+			return Err(AccountIdParseError::InvalidLength(hex.len()));
+		}
+		let mut bytes = [0; 32];
+		for (byte, digits) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+			let digits = core::str::from_utf8(digits).expect("hex digits are ascii");
+			*byte = u8::from_str_radix(digits, 16).expect("digits were checked to be hex");
+		}
+		Ok(Self(bytes))
+	}
+}
+
+// An account id is serialized as its address, so it stays readable in formats like JSON.
+#[cfg(feature = "serde")]
+impl serde::Serialize for AccountId {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AccountId {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let address = <String as serde::Deserialize>::deserialize(deserializer)?;
+		address.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+/// The error kinds which can be returned when dispatching a call in our runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchError {
+	/// The account does not have enough funds for the operation.
+	InsufficientBalance,
+	/// The operation would overflow a balance.
+	Overflow,
+	/// The account is frozen and cannot send funds.
+	AccountFrozen,
+	/// The funds are locked and cannot be sent yet.
+	FundsLocked,
+	/// The deposit is too low to create an account.
+	ExistentialDeposit,
+	/// The spender is not allowed to transfer that much on behalf of the owner.
+	InsufficientAllowance,
+	/// The content has already been claimed.
+	ClaimAlreadyExists,
+	/// The content has not been claimed.
+	ClaimNotFound,
+	/// The caller already owns the maximum number of claims.
+	TooManyClaims,
+	/// The caller is not the owner of the claim.
+	NotOwner,
+	/// The block number does not match what is expected.
+	InvalidBlockNumber,
+	/// The nonce of the extrinsic does not match what is expected for the caller.
+	InvalidNonce,
+	/// The nonce of the account is already at its maximum.
+	NonceOverflow,
+	/// The new timestamp is not greater than the current one.
+	TimestampNotIncreasing,
+	/// The asset has already been created.
+	AssetAlreadyExists,
+	/// The asset does not exist.
+	AssetNotFound,
+	/// The proposal has already been created.
+	ProposalAlreadyExists,
+	/// The proposal does not exist.
+	ProposalNotFound,
+	/// The caller has already voted on the proposal.
+	AlreadyVoted,
+	/// The block number cannot go below zero.
+	BlockNumberUnderflow,
+	/// The block number is already at its maximum.
+	BlockNumberOverflow,
+	/// The extrinsic would exceed the weight limit of the block.
+	ExhaustsResources,
+	/// The call can only be scheduled for a future block.
+	ScheduleInPast,
+	/// The account already has a vesting schedule.
+	VestingScheduleExists,
+	/// The account has no vesting schedule.
+	NotVesting,
+	/// The vesting schedule unlocks nothing per block, so its funds would never unlock.
+	VestingNeverUnlocks,
+	/// The caller is listed among the other signatories of the multisig.
+	SenderInSignatories,
+	/// The threshold of the multisig cannot be reached, or does not match the pending approvals.
+	InvalidThreshold,
+	/// The caller is not allowed to make this privileged call.
+	BadOrigin,
+	/// The item has already been minted.
+	ItemAlreadyExists,
+	/// The item does not exist.
+	ItemNotFound,
+	/// The transfer would leave the sender below the minimum balance, and reap its account.
+	WouldKillAccount,
+	/// The block has no extrinsics, and empty blocks are rejected.
+	EmptyBlock,
+	/// The amount of the transfer is more than the configured maximum.
+	TransferExceedsLimit,
+	/// The memo attached to a transfer is longer than the configured maximum.
+	MemoTooLong,
+	/// The account has already been created.
+	AccountExists,
+	/// The account has not been created.
+	AccountNotFound,
+	/// The account has received a drip from the faucet too recently.
+	FaucetCooldown,
+	/// The call has been paused, so it cannot be dispatched.
+	CallFiltered,
+	/// The burn would leave the account below the reserve floor.
+	BelowReserveFloor,
+	/// None of the funds requested to be unlocked have reached the end of their bonding period.
+	NothingUnlocked,
+}
+
+impl core::fmt::Display for DispatchError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let msg = match self {
+			Self::InsufficientBalance => "Not enough funds.",
+			Self::Overflow => "Funds exceed limit.",
+			Self::AccountFrozen => "account frozen",
+			Self::FundsLocked => "funds are locked",
+			Self::ExistentialDeposit => "value too low to create account",
+			Self::InsufficientAllowance => "allowance exceeded",
+			Self::ClaimAlreadyExists => "claim already exist",
+			Self::ClaimNotFound => "claim does not exist",
+			Self::TooManyClaims => "too many claims",
+			Self::NotOwner => "Caller is not the owner",
+			Self::InvalidBlockNumber => "block number does not match what is expected",
+			Self::InvalidNonce => "invalid nonce",
+			Self::NonceOverflow => "nonce overflow",
+			Self::TimestampNotIncreasing => "timestamp must increase",
+			Self::AssetAlreadyExists => "asset already exists",
+			Self::AssetNotFound => "asset does not exist",
+			Self::ProposalAlreadyExists => "proposal already exists",
+			Self::ProposalNotFound => "proposal does not exist",
+			Self::AlreadyVoted => "already voted",
+			Self::BlockNumberUnderflow => "block number underflow",
+			Self::BlockNumberOverflow => "block number overflow",
+			Self::ExhaustsResources => "block weight limit exceeded",
+			Self::ScheduleInPast => "cannot schedule in the past",
+			Self::VestingScheduleExists => "vesting schedule already exists",
+			Self::NotVesting => "account is not vesting",
+			Self::VestingNeverUnlocks => "vesting schedule never unlocks",
+			Self::SenderInSignatories => "caller is one of the other signatories",
+			Self::InvalidThreshold => "invalid multisig threshold",
+			Self::BadOrigin => "caller is not allowed to make this call",
+			Self::ItemAlreadyExists => "item already exists",
+			Self::ItemNotFound => "item does not exist",
+			Self::WouldKillAccount => "would kill account",
+			Self::EmptyBlock => "empty block",
+			Self::TransferExceedsLimit => "transfer exceeds limit",
+			Self::MemoTooLong => "memo too long",
+			Self::AccountExists => "account exists",
+			Self::AccountNotFound => "account does not exist",
+			Self::FaucetCooldown => "faucet cooldown",
+			Self::CallFiltered => "call filtered",
+			Self::BelowReserveFloor => "below reserve floor",
+			Self::NothingUnlocked => "no funds have unlocked",
+		};
+		f.write_str(msg)
+	}
+}
+
 /// The Result type for our runtime. When everything completes successfully, we return `Ok(())`,
-/// otherwise return a static error message.
-pub type DispatchResult = Result<(), &'static str>;
+/// otherwise return a `DispatchError` describing what went wrong.
+pub type DispatchResult = Result<(), DispatchError>;
+
+/// The origin of a call: either signed by an account, or unsigned, like the inherents which the
+/// block author puts into a block, such as the timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin<AccountId> {
+	/// The call is signed by the account.
+	Signed(AccountId),
+	/// The call is not signed by any account.
+	// The calls of the binary are all signed, but pallets expose unsigned calls which the tests
+	// dispatch with this origin.
+	#[allow(dead_code)]
+	None,
+}
+
+impl<AccountId> Origin<AccountId> {
+	/// The account which signed the call, if any.
+	pub fn signer(&self) -> Option<&AccountId> {
+		match self {
+			Self::Signed(who) => Some(who),
+			Self::None => None,
+		}
+	}
+
+	/// Ensure that the call is signed, returning the account which signed it.
+	pub fn ensure_signed(self) -> Result<AccountId, DispatchError> {
+		match self {
+			Self::Signed(who) => Ok(who),
+			Self::None => Err(DispatchError::BadOrigin),
+		}
+	}
+
+	/// Ensure that the call is not signed.
+	pub fn ensure_none(&self) -> DispatchResult {
+		match self {
+			Self::Signed(_) => Err(DispatchError::BadOrigin),
+			Self::None => Ok(()),
+		}
+	}
+}
 
 /// A trait which allows us to dispatch an incoming extrinsic to the appropriate state transition
 /// function call.
@@ -39,4 +447,432 @@ pub trait Dispatch {
 	/// This function takes a `caller` and the `call` they want to make, and returns a `Result`
 	/// based on the outcome of that function call.
 	fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
+
+	/// Check whether dispatching the `call` on behalf of the `caller` would succeed, without
+	/// changing any state. By default, every call is assumed to succeed.
+	fn can_dispatch(&self, _caller: &Self::Caller, _call: &Self::Call) -> DispatchResult {
+		Ok(())
+	}
+}
+
+/// A check which the runtime runs on every signed extrinsic before its call is dispatched, and
+/// which can reject it, like checking its nonce or whether its caller can pay the fee. It can read
+/// the whole `Runtime`, but cannot change it.
+///
+/// Several checks are composed into a pipeline as a tuple, which runs them in order and stops at
+/// the first which fails.
+pub trait SignedExtension<Runtime, Caller, Call> {
+	/// Check whether the `call` of the `caller` may be dispatched on top of the `runtime`.
+	fn validate(&self, runtime: &Runtime, caller: &Caller, call: &Call) -> DispatchResult;
+}
+
+impl<Runtime, Caller, Call> SignedExtension<Runtime, Caller, Call> for () {
+	fn validate(&self, _runtime: &Runtime, _caller: &Caller, _call: &Call) -> DispatchResult {
+		Ok(())
+	}
+}
+
+// Implement `SignedExtension` for tuples of signed extensions, running each of them in order.
+macro_rules! impl_signed_extension_for_tuple {
+	($($extension:ident),+) => {
+		impl<Runtime, Caller, Call, $($extension),+> SignedExtension<Runtime, Caller, Call>
+			for ($($extension,)+)
+		where
+			$($extension: SignedExtension<Runtime, Caller, Call>),+
+		{
+			#[allow(non_snake_case)]
+			fn validate(&self, runtime: &Runtime, caller: &Caller, call: &Call) -> DispatchResult {
+				let ($($extension,)+) = self;
+				$($extension.validate(runtime, caller, call)?;)+
+				Ok(())
+			}
+		}
+	};
+}
+
+impl_signed_extension_for_tuple!(A);
+impl_signed_extension_for_tuple!(A, B);
+impl_signed_extension_for_tuple!(A, B, C);
+impl_signed_extension_for_tuple!(A, B, C, D);
+impl_signed_extension_for_tuple!(A, B, C, D, E);
+impl_signed_extension_for_tuple!(A, B, C, D, E, F);
+
+/// The signed extension which checks that an extrinsic carries the next nonce of its caller, which
+/// is the nonce it carries. It is implemented for each runtime by the `runtime` macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckNonce<Nonce>(pub Nonce);
+
+/// The signed extension which checks that the caller of an extrinsic can pay the fee of its call.
+/// It is implemented by the `runtime` macro for each runtime which includes a `balances` pallet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CheckBalance;
+
+/// Apply `f` to the `state` as a transaction: if `f` fails, the `state` is restored to how it was
+/// before, so none of the changes made by `f` are kept.
+pub fn with_transaction<S: Clone, R, E>(
+	state: &mut S,
+	f: impl FnOnce(&mut S) -> Result<R, E>,
+) -> Result<R, E> {
+	let snapshot = state.clone();
+	let result = f(state);
+	if result.is_err() {
+		*state = snapshot;
+	}
+	result
+}
+
+/// The migrations of the storage of a pallet, which the runtime runs during an upgrade, to bring
+/// storage written by an older version of the pallet up to date.
+pub trait Migrate {
+	/// The version of the storage layout which the current code of the pallet uses.
+	const STORAGE_VERSION: u16 = 0;
+
+	/// Get the version of the storage layout of the state.
+	fn storage_version(&self) -> u16;
+
+	/// Set the version of the storage layout of the state.
+	fn set_storage_version(&mut self, version: u16);
+
+	/// Run the steps which transform the storage from version `from` to version `to`, and set the
+	/// storage version to `to`. By default, there are no steps to run.
+	fn migrate(&mut self, _from: u16, to: u16) {
+		self.set_storage_version(to);
+	}
+}
+
+/// Hooks which the runtime calls on each pallet while executing a block.
+///
+/// All hooks default to doing nothing, so pallets only implement the ones they need.
+pub trait Hooks<BlockNumber> {
+	/// Called at the start of block `n`, before any of its extrinsics are applied.
+	fn on_initialize(&mut self, _n: BlockNumber) {}
+
+	/// Called at the end of block `n`, after all of its extrinsics are applied.
+	fn on_finalize(&mut self, _n: BlockNumber) {}
+}
+
+/// A hook which the runtime calls on each pallet when an account is killed, because its stored
+/// balance was reaped, so the pallet can clean up what it keeps about the account.
+///
+/// It defaults to doing nothing, so pallets only implement it if they need to.
+pub trait OnKilledAccount<AccountId> {
+	/// Called after the account `who` is killed.
+	fn on_killed_account(&mut self, _who: &AccountId) {}
+}
+
+/// A hook which the runtime calls on each pallet when an account comes into existence, because it
+/// got its first provider, like a balance or a nonce, for example to grant it a welcome bonus.
+///
+/// It defaults to doing nothing, so pallets only implement it if they need to.
+pub trait OnNewAccount<AccountId> {
+	/// Called after the account `who` is created.
+	fn on_new_account(&mut self, _who: &AccountId) {}
+}
+
+/// A trait which allows the runtime to collect the events buffered by a pallet, so they can be
+/// deposited into the event buffer of the system pallet.
+///
+/// Pallets which do not emit any events can rely on the default implementation.
+pub trait TakeEvents<Event> {
+	/// Remove and return all the events the pallet has emitted so far.
+	fn take_events(&mut self) -> Vec<Event> {
+		Vec::new()
+	}
+}
+
+/// A simple storage map used by the pallets to keep their state.
+/// It wraps a `BTreeMap`, and keeps the behavior of returning a default value for missing keys in
+/// one place.
+#[derive(Clone)]
+pub struct StorageMap<K, V> {
+	map: BTreeMap<K, V>,
+}
+
+impl<K: Ord, V> StorageMap<K, V> {
+	/// Create a new empty storage map.
+	pub fn new() -> Self {
+		Self { map: BTreeMap::new() }
+	}
+
+	/// Get the value stored under `key`, if any.
+	pub fn get<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&V>
+	where
+		K: Borrow<Q>,
+	{
+		self.map.get(key)
+	}
+
+	/// Get a copy of the value stored under `key`.
+	/// If there is no value stored under `key`, we return the default value.
+	pub fn get_or_default<Q: ?Sized + Ord>(&self, key: &Q) -> V
+	where
+		K: Borrow<Q>,
+		V: Default + Clone,
+	{
+		self.map.get(key).cloned().unwrap_or_default()
+	}
+
+	/// Get a mutable reference to the value stored under `key`, if any.
+	pub fn get_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&mut V>
+	where
+		K: Borrow<Q>,
+	{
+		self.map.get_mut(key)
+	}
+
+	/// Store `value` under `key`, returning the previous value if there was one.
+	pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+		self.map.insert(key, value)
+	}
+
+	/// Remove the value stored under `key`, returning it if there was one.
+	pub fn remove<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<V>
+	where
+		K: Borrow<Q>,
+	{
+		self.map.remove(key)
+	}
+
+	/// Check whether there is a value stored under `key`.
+	pub fn contains_key<Q: ?Sized + Ord>(&self, key: &Q) -> bool
+	where
+		K: Borrow<Q>,
+	{
+		self.map.contains_key(key)
+	}
+
+	/// Only keep the entries for which `f` returns `true`.
+	pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, f: F) {
+		self.map.retain(f)
+	}
+
+	/// Get the number of entries in the map.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Iterate over all the entries of the map, in sorted key order.
+	pub fn iter(&self) -> btree_map::Iter<'_, K, V> {
+		self.map.iter()
+	}
+
+	/// Iterate over all the values of the map, in sorted key order.
+	pub fn values(&self) -> btree_map::Values<'_, K, V> {
+		self.map.values()
+	}
+
+	/// Store each entry under a new key, given by `f` from its current key, for example during a
+	/// migration. If two entries get the same key, the first one in sorted key order is kept.
+	/// Returns the entries which were left out for that reason, under their current key.
+	pub fn rekey<F: FnMut(&K) -> K>(&mut self, mut f: F) -> Vec<(K, V)> {
+		let mut collisions = Vec::new();
+		for (key, value) in core::mem::take(&mut self.map) {
+			match self.map.entry(f(&key)) {
+				btree_map::Entry::Vacant(entry) => {
+					entry.insert(value);
+				},
+				btree_map::Entry::Occupied(_) => collisions.push((key, value)),
+			}
+		}
+		collisions
+	}
+}
+
+impl<K: Debug, V: Debug> Debug for StorageMap<K, V> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		self.map.fmt(f)
+	}
+}
+
+// A storage map is serialized as a sequence of `(key, value)` entries rather than as a map, since
+// formats like JSON only support string keys.
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize, V: serde::Serialize> serde::Serialize for StorageMap<K, V> {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(self.map.iter())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for StorageMap<K, V>
+where
+	K: Ord + serde::Deserialize<'de>,
+	V: serde::Deserialize<'de>,
+{
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let entries = <Vec<(K, V)> as serde::Deserialize>::deserialize(deserializer)?;
+		Ok(Self { map: entries.into_iter().collect() })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		with_transaction, AccountId, AccountIdParseError, DispatchError, DispatchResult, Perbill,
+		SignedExtension, StableHasher, StorageMap,
+	};
+
+	#[test]
+	fn storage_map() {
+		let mut map = StorageMap::<&'static str, u32>::new();
+		assert!(map.get(&"alice").is_none());
+		assert!(!map.contains_key(&"alice"));
+
+		assert_eq!(map.insert("alice", 10), None);
+		assert_eq!(map.insert("bob", 20), None);
+		assert_eq!(map.insert("alice", 15), Some(10));
+		assert_eq!(map.get(&"alice"), Some(&15));
+		assert!(map.contains_key(&"bob"));
+
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&"alice", &15), (&"bob", &20)]);
+		assert_eq!(map.values().collect::<Vec<_>>(), vec![&15, &20]);
+
+		assert_eq!(map.remove(&"alice"), Some(15));
+		assert_eq!(map.remove(&"alice"), None);
+		assert!(!map.contains_key(&"alice"));
+	}
+
+	#[test]
+	fn storage_map_default() {
+		let mut map = StorageMap::<&'static str, u32>::new();
+		assert_eq!(map.get_or_default(&"alice"), 0);
+
+		map.insert("alice", 10);
+		assert_eq!(map.get_or_default(&"alice"), 10);
+		assert_eq!(map.get_or_default(&"bob"), 0);
+	}
+
+	#[test]
+	fn storage_map_rekey() {
+		let mut map = StorageMap::<u32, &'static str>::new();
+		map.insert(1, "one");
+		map.insert(2, "two");
+		map.insert(3, "three");
+
+		// The keys 2 and 3 both become 1: the first of them is kept, and the other is returned.
+		assert_eq!(map.rekey(|key| key / 2), vec![(3, "three")]);
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&0, &"one"), (&1, &"two")]);
+		assert!(map.rekey(|key| key + 1).is_empty());
+		assert_eq!(map.iter().collect::<Vec<_>>(), vec![(&1, &"one"), (&2, &"two")]);
+	}
+
+	#[test]
+	fn transaction() {
+		let mut map = StorageMap::<&'static str, u32>::new();
+		map.insert("alice", 10);
+
+		let result = with_transaction(&mut map, |map| {
+			map.insert("alice", 5);
+			map.insert("bob", 5);
+			Err::<(), _>("failed")
+		});
+		assert_eq!(result, Err("failed"));
+		assert_eq!(map.get(&"alice"), Some(&10));
+		assert!(!map.contains_key(&"bob"));
+
+		let result = with_transaction(&mut map, |map| {
+			map.insert("bob", 5);
+			Ok::<_, ()>(map.len())
+		});
+		assert_eq!(result, Ok(2));
+		assert_eq!(map.get(&"bob"), Some(&5));
+	}
+
+	#[test]
+	fn perbill() {
+		assert_eq!(Perbill::from_percent(25).mul_floor(200u128), 50);
+		assert_eq!(Perbill::from_percent(0).mul_floor(200u128), 0);
+		assert_eq!(Perbill::from_percent(100).mul_floor(200u128), 200);
+		assert_eq!(Perbill::from_percent(150), Perbill::ONE);
+		assert_eq!(Perbill::from_parts(2_000_000_000), Perbill::ONE);
+
+		// The result is rounded down when the amount does not divide evenly.
+		assert_eq!(Perbill::from_percent(25).mul_floor(7u128), 1);
+		assert_eq!(Perbill::from_percent(1).mul_floor(99u128), 0);
+		assert_eq!(Perbill::from_percent(33).mul_floor(10u64), 3);
+		// Large amounts do not overflow.
+		assert_eq!(Perbill::from_percent(50).mul_floor(u128::MAX), u128::MAX / 2);
+
+		assert_eq!(Perbill::from_percent(25).saturating_reciprocal_mul(50u128), 200);
+		assert_eq!(Perbill::from_percent(30).saturating_reciprocal_mul(10u128), 33);
+		assert_eq!(Perbill::ZERO.saturating_reciprocal_mul(10u64), u64::MAX);
+		assert_eq!(Perbill::ZERO.saturating_reciprocal_mul(0u64), 0);
+		assert_eq!(Perbill::from_percent(10).saturating_reciprocal_mul(u64::MAX), u64::MAX);
+	}
+
+	#[test]
+	fn stable_hasher() {
+		use core::hash::Hasher;
+
+		// The known FNV-1a hashes of these bytes.
+		assert_eq!(StableHasher::default().finish(), 0xcbf2_9ce4_8422_2325);
+		let mut hasher = StableHasher::default();
+		hasher.write(b"a");
+		assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+	}
+
+	#[test]
+	fn account_id_address() {
+		let accounts =
+			[AccountId::default(), AccountId::from_name("alice"), AccountId::new([0xab; 32])];
+		for account in accounts {
+			let address = account.to_string();
+			assert_eq!(address.len(), 66);
+			assert_eq!(address.parse(), Ok(account));
+		}
+
+		let alice = AccountId::from_name("alice");
+		assert_eq!(
+			alice.to_string(),
+			"0x616c696365000000000000000000000000000000000000000000000000000000"
+		);
+		assert_eq!(format!("{:?}", alice), alice.to_string());
+		// Upper case hex digits are accepted too.
+		assert_eq!(alice.to_string().to_uppercase().replacen("0X", "0x", 1).parse(), Ok(alice));
+	}
+
+	#[test]
+	fn malformed_address() {
+		let address = AccountId::from_name("alice").to_string();
+
+		assert_eq!(address[2..].parse::<AccountId>(), Err(AccountIdParseError::MissingPrefix));
+		assert_eq!(address[..64].parse::<AccountId>(), Err(AccountIdParseError::InvalidLength(62)));
+		assert_eq!(
+			format!("{}00", address).parse::<AccountId>(),
+			Err(AccountIdParseError::InvalidLength(66))
+		);
+		assert_eq!(
+			address.replacen('6', "g", 1).parse::<AccountId>(),
+			Err(AccountIdParseError::InvalidCharacter('g'))
+		);
+		assert_eq!(
+			"0xalice".parse::<AccountId>().unwrap_err().to_string(),
+			"invalid hex digit 'l' in address"
+		);
+	}
+
+	#[test]
+	fn signed_extension_pipeline() {
+		struct Fails(DispatchError);
+		impl SignedExtension<(), u32, ()> for Fails {
+			fn validate(&self, _: &(), _: &u32, _: &()) -> DispatchResult {
+				Err(self.0)
+			}
+		}
+		struct Unreachable;
+		impl SignedExtension<(), u32, ()> for Unreachable {
+			fn validate(&self, _: &(), _: &u32, _: &()) -> DispatchResult {
+				panic!("the pipeline did not stop at the first failure")
+			}
+		}
+
+		assert_eq!(().validate(&(), &1, &()), Ok(()));
+		assert_eq!(((), ()).validate(&(), &1, &()), Ok(()));
+		// The checks run in order, and the first which fails stops the pipeline.
+		let pipeline = ((), Fails(DispatchError::InvalidNonce), Unreachable);
+		assert_eq!(pipeline.validate(&(), &1, &()), Err(DispatchError::InvalidNonce));
+		let pipeline =
+			(Fails(DispatchError::InvalidNonce), Fails(DispatchError::InsufficientBalance));
+		assert_eq!(pipeline.validate(&(), &1, &()), Err(DispatchError::InvalidNonce));
+	}
 }
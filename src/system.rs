@@ -1,30 +1,142 @@
-use std::{
-	collections::BTreeMap,
-	ops::{Add, AddAssign},
-};
+use num::{CheckedAdd, CheckedSub, One, Zero};
+use std::collections::{BTreeSet, VecDeque};
 
-use num::{One, Zero};
+use crate::support::{DispatchError, DispatchResult, StorageMap, Weight};
 
 pub trait Config {
 	type AccountId: Ord + Clone;
-	type BlockNumber: AddAssign + Copy + Zero + One;
-	type Nonce: Zero + One + Add + Copy;
+	type BlockNumber: CheckedAdd + CheckedSub + Copy + Zero + One + Ord + Into<u64>;
+	type Nonce: Zero + One + CheckedAdd + Copy + Default;
+	/// The aggregated event type of the runtime, which the system pallet keeps a buffer of.
+	type RuntimeEvent;
+	/// The type used to represent the hash of a block, or of a call.
+	type Hash: Ord + Clone;
+
+	/// The number of recent block hashes to keep. Older block hashes are pruned whenever the block
+	/// number is incremented. `None` keeps every block hash.
+	fn block_hash_count() -> Option<Self::BlockNumber> {
+		None
+	}
+
+	/// The default maximum total weight of the extrinsics in a block.
+	fn max_block_weight() -> Weight {
+		Weight::MAX
+	}
+
+	/// Whether blocks without any extrinsics are rejected by default.
+	fn reject_empty_blocks() -> bool {
+		false
+	}
+
+	/// The maximum number of events kept in the event buffer. Once it is full, the oldest event is
+	/// dropped for each new one. `None` keeps every event of the block.
+	fn max_events() -> Option<usize> {
+		None
+	}
+
+	/// The time between two blocks, in milliseconds. Blocks are assumed to be produced at this
+	/// fixed interval.
+	fn block_time_ms() -> u64 {
+		6_000
+	}
+}
+
+/// The phase of the execution of a block, during which an event was deposited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Phase {
+	/// The block is being initialized, before any of its extrinsics are applied.
+	#[default]
+	Initialization,
+	/// The extrinsic at this index in the block is being applied.
+	ApplyExtrinsic(u32),
+	/// The block is being finalized, after all of its extrinsics are applied.
+	Finalization,
+}
+
+/// An event, with the phase of the block during which it was deposited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRecord<Event> {
+	/// The phase of the block during which the event was deposited.
+	pub phase: Phase,
+	/// The event itself.
+	pub event: Event,
 }
 
 /// This is the System Pallet.
 /// It handles low level state needed for your blockchain.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::BlockNumber: serde::Serialize, \
+			T::Nonce: serde::Serialize, T::Hash: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, \
+			T::BlockNumber: serde::Deserialize<'de>, T::Nonce: serde::Deserialize<'de>, \
+			T::Hash: serde::Deserialize<'de>",
+	))
+)]
 pub struct Pallet<T: Config> {
 	/// The current block number.
 	block_number: T::BlockNumber,
 	/// A map from an account to their nonce.
-	nonce: BTreeMap<T::AccountId, T::Nonce>,
+	nonce: StorageMap<T::AccountId, T::Nonce>,
+	/// A map from an account to the number of references other pallets hold on it, for example
+	/// because of locked funds. An account with consumers should not be reaped.
+	consumers: StorageMap<T::AccountId, u32>,
+	/// A map from an account to the number of things which keep it alive, like its nonce or its
+	/// balance. An account exists as long as it has providers.
+	providers: StorageMap<T::AccountId, u32>,
+	/// The number of accounts which exist, so they can be counted without going through them.
+	account_count: u32,
+	/// A map from a recent block number to the hash of that block.
+	block_hash: StorageMap<T::BlockNumber, T::Hash>,
+	/// The number of extrinsics applied in the current block.
+	extrinsic_count: u32,
+	/// The total weight of the extrinsics applied in the current block.
+	block_weight: Weight,
+	/// The maximum total weight of the extrinsics in a block.
+	max_block_weight: Weight,
+	/// Whether blocks without any extrinsics are rejected.
+	reject_empty_blocks: bool,
+	/// The calls which cannot be dispatched, by the names of their pallet and function.
+	paused_calls: BTreeSet<(String, String)>,
+	/// The current phase of the execution of the block.
+	/// This only describes the current block, so it is not part of the serialized state.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	phase: Phase,
+	/// The events deposited during the current block, with the phase they were deposited in.
+	/// These only describe the current block, so they are not part of the serialized state.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	events: VecDeque<EventRecord<T::RuntimeEvent>>,
+	/// The accounts which came into existence, which have not yet been collected by the runtime.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	new_accounts: Vec<T::AccountId>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
 }
 
 impl<T: Config> Pallet<T> {
 	/// Create a new instance of the System Pallet.
 	pub fn new() -> Self {
-		Self { block_number: T::BlockNumber::zero(), nonce: BTreeMap::new() }
+		Self {
+			block_number: T::BlockNumber::zero(),
+			nonce: StorageMap::new(),
+			consumers: StorageMap::new(),
+			providers: StorageMap::new(),
+			account_count: 0,
+			block_hash: StorageMap::new(),
+			extrinsic_count: 0,
+			block_weight: Weight::default(),
+			max_block_weight: T::max_block_weight(),
+			reject_empty_blocks: T::reject_empty_blocks(),
+			paused_calls: BTreeSet::new(),
+			phase: Phase::Initialization,
+			events: VecDeque::new(),
+			new_accounts: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
 	}
 
 	/// Get the current block number.
@@ -32,38 +144,670 @@ impl<T: Config> Pallet<T> {
 		self.block_number
 	}
 
+	/// Estimate the time elapsed since the start of the chain, in milliseconds, from the current
+	/// block number and the block time. This saturates at `u64::MAX`.
+	#[cfg(test)]
+	pub fn estimated_elapsed_ms(&self) -> u64 {
+		self.block_number.into().saturating_mul(T::block_time_ms())
+	}
+
+	/// Get the nonce of an account `who`.
+	/// If the account has never made a transaction, we return zero.
+	pub fn nonce(&self, who: &T::AccountId) -> T::Nonce {
+		self.nonce.get_or_default(who)
+	}
+
+	/// Get the nonce which the next transaction of the account `who` must carry: the one after its
+	/// current nonce, which is one for an account which has never made a transaction.
+	/// If the current nonce is already the largest one, it is returned unchanged, and a transaction
+	/// carrying it is rejected.
+	pub fn next_nonce(&self, who: &T::AccountId) -> T::Nonce {
+		let nonce = self.nonce(who);
+		nonce.checked_add(&T::Nonce::one()).unwrap_or(nonce)
+	}
+
+	/// Reset the nonce of an account `who` back to zero, by removing it from storage.
+	/// This does nothing if the account has never made a transaction.
+	#[cfg(test)]
+	pub fn reset_nonce(&mut self, who: &T::AccountId) {
+		if self.nonce.remove(who).is_some() {
+			self.dec_providers(who);
+		}
+	}
+
+	/// Get the nonce of every account which has made a transaction, in sorted account order.
+	pub fn all_nonces(&self) -> Vec<(T::AccountId, T::Nonce)> {
+		self.nonce.iter().map(|(who, nonce)| (who.clone(), *nonce)).collect()
+	}
+
 	// This function can be used to increment the block number.
-	// Increases the block number by one.
-	pub fn inc_block_number(&mut self) {
-		self.block_number += T::BlockNumber::one();
+	// Increases the block number by one, clears the events, the extrinsic count and the weight of
+	// the previous block, prunes the block hashes which fall outside the `block_hash_count`
+	// window, and enters the initialization phase of the new block.
+	// This function will return an error, without changing any state, if the block number is
+	// already at its maximum.
+	pub fn inc_block_number(&mut self) -> DispatchResult {
+		self.block_number = self
+			.block_number
+			.checked_add(&T::BlockNumber::one())
+			.ok_or(DispatchError::BlockNumberOverflow)?;
+		self.extrinsic_count = 0;
+		self.block_weight = Weight::default();
+		self.reset_events();
+		self.prune_block_hashes();
+		self.phase = Phase::Initialization;
+		Ok(())
+	}
+
+	/// Set the current block number to `n`.
+	pub fn set_block_number(&mut self, n: T::BlockNumber) {
+		self.block_number = n;
+	}
+
+	/// Decrease the block number by one.
+	/// This function will return an error if the block number is already zero.
+	#[cfg(test)]
+	pub fn dec_block_number(&mut self) -> DispatchResult {
+		self.block_number = self
+			.block_number
+			.checked_sub(&T::BlockNumber::one())
+			.ok_or(DispatchError::BlockNumberUnderflow)?;
+		Ok(())
 	}
 
 	// Increment the nonce of an account. This helps us keep track of how many transactions each
 	// account has made.
-	pub fn inc_nonce(&mut self, who: &T::AccountId) {
-		let nonce = self.nonce.get(who).unwrap_or(&T::Nonce::zero()).add(T::Nonce::one());
-		self.nonce.insert(who.clone(), nonce);
+	// This function will return an error if the nonce of the account is already at its maximum.
+	pub fn inc_nonce(&mut self, who: &T::AccountId) -> DispatchResult {
+		let nonce = self
+			.nonce(who)
+			.checked_add(&T::Nonce::one())
+			.ok_or(DispatchError::NonceOverflow)?;
+		// The first nonce of an account keeps it alive.
+		if self.nonce.insert(who.clone(), nonce).is_none() {
+			self.inc_providers(who);
+		}
+		Ok(())
+	}
+
+	/// Set the nonce of the account `who` directly, for example to seed it at genesis, so its next
+	/// transaction carries the nonce after it. Setting the first nonce of an account keeps it
+	/// alive.
+	pub fn set_nonce(&mut self, who: &T::AccountId, nonce: T::Nonce) {
+		if self.nonce.insert(who.clone(), nonce).is_none() {
+			self.inc_providers(who);
+		}
+	}
+
+	/// Check whether the account `who` exists, which is the case once it has a nonce entry.
+	pub fn account_exists(&self, who: &T::AccountId) -> bool {
+		self.nonce.contains_key(who)
+	}
+
+	/// Create the account `who` explicitly, before it has made any transaction, by giving it a
+	/// zero nonce entry.
+	/// This function will return an error if the account already exists.
+	pub fn create_account(&mut self, who: &T::AccountId) -> DispatchResult {
+		if self.account_exists(who) {
+			return Err(DispatchError::AccountExists);
+		}
+		self.nonce.insert(who.clone(), T::Nonce::zero());
+		self.inc_providers(who);
+		Ok(())
+	}
+
+	/// Get the number of consumers of the account `who`.
+	pub fn consumers(&self, who: &T::AccountId) -> u32 {
+		self.consumers.get_or_default(who)
+	}
+
+	/// Increment the number of consumers of the account `who`.
+	#[cfg(test)]
+	pub fn inc_consumers(&mut self, who: &T::AccountId) {
+		let consumers = self.consumers(who).saturating_add(1);
+		self.consumers.insert(who.clone(), consumers);
+	}
+
+	/// Decrement the number of consumers of the account `who`.
+	/// This does nothing if the account has no consumers.
+	#[cfg(test)]
+	pub fn dec_consumers(&mut self, who: &T::AccountId) {
+		match self.consumers(who) {
+			0 => {},
+			1 => {
+				self.consumers.remove(who);
+			},
+			consumers => {
+				self.consumers.insert(who.clone(), consumers - 1);
+			},
+		}
+	}
+
+	/// Get the number of providers of the account `who`.
+	pub fn providers(&self, who: &T::AccountId) -> u32 {
+		self.providers.get_or_default(who)
+	}
+
+	/// Increment the number of providers of the account `who`. The account is created when it
+	/// gets its first provider.
+	pub fn inc_providers(&mut self, who: &T::AccountId) {
+		let providers = self.providers(who).saturating_add(1);
+		if providers == 1 {
+			self.account_count = self.account_count.saturating_add(1);
+			self.new_accounts.push(who.clone());
+		}
+		self.providers.insert(who.clone(), providers);
+	}
+
+	/// Decrement the number of providers of the account `who`. The account is reaped when it loses
+	/// its last provider, and this returns whether it was.
+	/// This does nothing if the account has no providers.
+	pub fn dec_providers(&mut self, who: &T::AccountId) -> bool {
+		match self.providers(who) {
+			0 => false,
+			1 => {
+				self.providers.remove(who);
+				self.account_count = self.account_count.saturating_sub(1);
+				true
+			},
+			providers => {
+				self.providers.insert(who.clone(), providers - 1);
+				false
+			},
+		}
+	}
+
+	/// Take the accounts which came into existence, by getting their first provider, since the
+	/// last call, so the runtime can pass them on to the other pallets.
+	pub fn take_new_accounts(&mut self) -> Vec<T::AccountId> {
+		core::mem::take(&mut self.new_accounts)
+	}
+
+	/// Get the number of accounts which exist, which are the accounts with providers.
+	pub fn account_count(&self) -> u32 {
+		self.account_count
+	}
+
+	/// Check whether the account `who` can be reaped, which is only the case when it has no
+	/// consumers.
+	#[cfg(test)]
+	pub fn can_reap(&self, who: &T::AccountId) -> bool {
+		self.consumers(who) == 0
+	}
+
+	/// Note that an extrinsic has been applied in the current block.
+	pub fn note_extrinsic(&mut self) {
+		self.extrinsic_count = self.extrinsic_count.saturating_add(1);
+	}
+
+	/// Get the number of extrinsics applied in the current block.
+	#[cfg(test)]
+	pub fn extrinsic_count(&self) -> u32 {
+		self.extrinsic_count
+	}
+
+	/// Get the total weight of the extrinsics applied in the current block.
+	#[cfg(test)]
+	pub fn block_weight(&self) -> Weight {
+		self.block_weight
+	}
+
+	/// Get the maximum total weight of the extrinsics in a block.
+	#[cfg(test)]
+	pub fn max_block_weight(&self) -> Weight {
+		self.max_block_weight
+	}
+
+	/// Set the maximum total weight of the extrinsics in a block.
+	#[cfg(test)]
+	pub fn set_max_block_weight(&mut self, weight: Weight) {
+		self.max_block_weight = weight;
+	}
+
+	/// Check whether blocks without any extrinsics are rejected.
+	pub fn reject_empty_blocks(&self) -> bool {
+		self.reject_empty_blocks
+	}
+
+	/// Set whether blocks without any extrinsics are rejected.
+	#[cfg(test)]
+	pub fn set_reject_empty_blocks(&mut self, reject: bool) {
+		self.reject_empty_blocks = reject;
+	}
+
+	/// Pause the `call` function of the `pallet`, so it cannot be dispatched until it is resumed.
+	pub fn pause_call(&mut self, pallet: &str, call: &str) {
+		self.paused_calls.insert((pallet.to_string(), call.to_string()));
+	}
+
+	/// Resume the `call` function of the `pallet`, after it was paused.
+	pub fn resume_call(&mut self, pallet: &str, call: &str) {
+		self.paused_calls.remove(&(pallet.to_string(), call.to_string()));
+	}
+
+	/// Check whether the `call` function of the `pallet` is paused.
+	pub fn is_call_paused(&self, pallet: &str, call: &str) -> bool {
+		self.paused_calls.contains(&(pallet.to_string(), call.to_string()))
+	}
+
+	/// Add `weight` to the weight of the current block.
+	/// This function will return an error, without changing the block weight, if it would exceed
+	/// the maximum block weight.
+	pub fn register_weight(&mut self, weight: Weight) -> DispatchResult {
+		let block_weight = self.block_weight.saturating_add(weight);
+		if block_weight > self.max_block_weight {
+			return Err(DispatchError::ExhaustsResources)
+		}
+		self.block_weight = block_weight;
+		Ok(())
+	}
+
+	/// Store the `hash` of the block `number`.
+	#[cfg(test)]
+	pub fn set_block_hash(&mut self, number: T::BlockNumber, hash: T::Hash) {
+		self.block_hash.insert(number, hash);
+	}
+
+	/// Get the hash (if any) of the block `number`.
+	#[cfg(test)]
+	pub fn block_hash(&self, number: &T::BlockNumber) -> Option<&T::Hash> {
+		self.block_hash.get(number)
+	}
+
+	// Remove the hashes of the blocks which are `block_hash_count` or more blocks older than the
+	// current block.
+	fn prune_block_hashes(&mut self) {
+		let Some(count) = T::block_hash_count() else { return };
+		if let Some(oldest_to_prune) = self.block_number.checked_sub(&count) {
+			self.block_hash.retain(|number, _| *number > oldest_to_prune);
+		}
+	}
+
+	/// Get the current phase of the execution of the block.
+	#[cfg(test)]
+	pub fn phase(&self) -> Phase {
+		self.phase
+	}
+
+	/// Set the current phase of the execution of the block, which the events deposited from now
+	/// on are tagged with.
+	pub fn set_phase(&mut self, phase: Phase) {
+		self.phase = phase;
+	}
+
+	/// Deposit an event into the event buffer of the current block, tagged with the current phase.
+	/// If the buffer is full, the oldest event in it is dropped.
+	pub fn deposit_event(&mut self, event: T::RuntimeEvent) {
+		if let Some(max_events) = T::max_events() {
+			if max_events == 0 {
+				return;
+			}
+			while self.events.len() >= max_events {
+				self.events.pop_front();
+			}
+		}
+		self.events.push_back(EventRecord { phase: self.phase, event });
+	}
+
+	/// Get the events deposited during the current block.
+	#[cfg(test)]
+	pub fn events(&self) -> Vec<&T::RuntimeEvent> {
+		self.events.iter().map(|record| &record.event).collect()
+	}
+
+	/// Get the events deposited during the current block, with the phase they were deposited in.
+	pub fn event_records(&self) -> Vec<&EventRecord<T::RuntimeEvent>> {
+		self.events.iter().collect()
+	}
+
+	// Clear the event buffer, which happens at the start of each block.
+	fn reset_events(&mut self) {
+		self.events.clear();
+	}
+}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
 	}
 }
 
 #[cfg(test)]
 mod test {
+	use crate::support::{DispatchError, Weight};
+
 	struct TestConfig;
 
 	impl super::Config for TestConfig {
 		type AccountId = &'static str;
 		type BlockNumber = u32;
 		type Nonce = u32;
+		type RuntimeEvent = &'static str;
+		type Hash = u64;
+
+		fn block_hash_count() -> Option<Self::BlockNumber> {
+			Some(2)
+		}
+	}
+
+	// A configuration which keeps at most 3 events.
+	struct MaxEventsConfig;
+
+	impl super::Config for MaxEventsConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = &'static str;
+		type Hash = u64;
+
+		fn max_events() -> Option<usize> {
+			Some(3)
+		}
+	}
+
+	// A configuration with a small nonce type, to test its overflow.
+	struct SmallNonceConfig;
+
+	impl super::Config for SmallNonceConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u8;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	// A configuration with a small block number type, to test its overflow.
+	struct SmallBlockNumberConfig;
+
+	impl super::Config for SmallBlockNumberConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u8;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
 	}
 
 	#[test]
 	fn init_system() {
 		let mut system = super::Pallet::<TestConfig>::new();
 		let alice = "alice";
-		system.inc_block_number();
-		system.inc_nonce(&alice);
+		system.inc_block_number().unwrap();
+		system.inc_nonce(&alice).unwrap();
 
 		assert_eq!(system.block_number(), 1);
 		assert_eq!(system.nonce.get(alice).unwrap(), &1);
 	}
+
+	#[test]
+	fn nonce_of_account() {
+		let mut system = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(system.nonce(&"alice"), 0);
+
+		system.inc_nonce(&"alice").unwrap();
+		system.inc_nonce(&"alice").unwrap();
+		assert_eq!(system.nonce(&"alice"), 2);
+		assert_eq!(system.nonce(&"bob"), 0);
+	}
+
+	#[test]
+	fn next_nonce() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert_eq!(system.next_nonce(&"alice"), 1);
+
+		system.set_nonce(&"alice", 3);
+		assert_eq!(system.next_nonce(&"alice"), 4);
+
+		// The largest nonce has no next one.
+		system.set_nonce(&"alice", u32::MAX);
+		assert_eq!(system.next_nonce(&"alice"), u32::MAX);
+	}
+
+	#[test]
+	fn account_exists() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert!(!system.account_exists(&"alice"));
+
+		system.inc_nonce(&"alice").unwrap();
+		assert!(system.account_exists(&"alice"));
+		assert!(!system.account_exists(&"bob"));
+	}
+
+	#[test]
+	fn create_account() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		assert_eq!(system.create_account(&alice), Ok(()));
+		assert!(system.account_exists(&alice));
+		assert_eq!(system.nonce(&alice), 0);
+		assert_eq!(system.account_count(), 1);
+		assert_eq!(system.create_account(&alice), Err(DispatchError::AccountExists));
+
+		// The first transaction of a created account does not create it again.
+		system.inc_nonce(&alice).unwrap();
+		assert_eq!(system.account_count(), 1);
+		assert_eq!(system.create_account(&"bob"), Ok(()));
+		assert_eq!(system.account_count(), 2);
+		assert_eq!(system.take_new_accounts(), vec!["alice", "bob"]);
+		assert!(system.take_new_accounts().is_empty());
+	}
+
+	#[test]
+	fn consumers_lifecycle() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		assert_eq!(system.consumers(&alice), 0);
+		assert!(system.can_reap(&alice));
+
+		system.inc_consumers(&alice);
+		system.inc_consumers(&alice);
+		assert_eq!(system.consumers(&alice), 2);
+		assert!(!system.can_reap(&alice));
+
+		system.dec_consumers(&alice);
+		assert_eq!(system.consumers(&alice), 1);
+		assert!(!system.can_reap(&alice));
+
+		system.dec_consumers(&alice);
+		assert_eq!(system.consumers(&alice), 0);
+		assert!(system.can_reap(&alice));
+		assert!(!system.consumers.contains_key(&alice));
+
+		// Decrementing without consumers does nothing.
+		system.dec_consumers(&alice);
+		assert_eq!(system.consumers(&alice), 0);
+	}
+
+	#[test]
+	fn set_and_dec_block_number() {
+		let mut system = super::Pallet::<TestConfig>::new();
+
+		system.set_block_number(10);
+		assert_eq!(system.block_number(), 10);
+
+		assert!(system.dec_block_number().is_ok());
+		assert!(system.dec_block_number().is_ok());
+		assert_eq!(system.block_number(), 8);
+	}
+
+	#[test]
+	fn dec_block_number_from_zero() {
+		let mut system = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(system.dec_block_number(), Err(DispatchError::BlockNumberUnderflow));
+		assert_eq!(system.block_number(), 0);
+	}
+
+	#[test]
+	fn deposit_events() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert!(system.events().is_empty());
+
+		system.deposit_event("first");
+		system.deposit_event("second");
+		assert_eq!(system.events(), vec![&"first", &"second"]);
+
+		system.set_phase(super::Phase::ApplyExtrinsic(0));
+		system.deposit_event("third");
+		assert_eq!(
+			system.event_records().last().copied(),
+			Some(&super::EventRecord { phase: super::Phase::ApplyExtrinsic(0), event: "third" })
+		);
+		assert_eq!(system.event_records()[0].phase, super::Phase::Initialization);
+
+		// Starting a new block clears the events of the previous one.
+		system.inc_block_number().unwrap();
+		assert!(system.events().is_empty());
+		assert_eq!(system.phase(), super::Phase::Initialization);
+	}
+
+	#[test]
+	fn block_hashes() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert!(system.block_hash(&1).is_none());
+
+		system.inc_block_number().unwrap();
+		system.set_block_hash(1, 0x11);
+		system.inc_block_number().unwrap();
+		system.set_block_hash(2, 0x22);
+		assert_eq!(system.block_hash(&1), Some(&0x11));
+		assert_eq!(system.block_hash(&2), Some(&0x22));
+
+		// Only the hashes of the last 2 blocks are kept.
+		system.inc_block_number().unwrap();
+		assert!(system.block_hash(&1).is_none());
+		assert_eq!(system.block_hash(&2), Some(&0x22));
+
+		system.inc_block_number().unwrap();
+		assert!(system.block_hash(&2).is_none());
+	}
+
+	#[test]
+	fn extrinsic_count() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert_eq!(system.extrinsic_count(), 0);
+
+		system.note_extrinsic();
+		system.note_extrinsic();
+		assert_eq!(system.extrinsic_count(), 2);
+
+		system.inc_block_number().unwrap();
+		assert_eq!(system.extrinsic_count(), 0);
+	}
+
+	#[test]
+	fn register_weight() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert_eq!(system.max_block_weight(), Weight::MAX);
+
+		system.set_max_block_weight(Weight::new(10));
+		assert_eq!(system.register_weight(Weight::new(6)), Ok(()));
+		assert_eq!(system.register_weight(Weight::new(5)), Err(DispatchError::ExhaustsResources));
+		assert_eq!(system.register_weight(Weight::new(4)), Ok(()));
+		assert_eq!(system.block_weight(), Weight::new(10));
+
+		// Starting a new block resets the block weight.
+		system.inc_block_number().unwrap();
+		assert_eq!(system.block_weight(), Weight::default());
+	}
+
+	#[test]
+	fn nonce_overflow() {
+		let mut system = super::Pallet::<SmallNonceConfig>::new();
+
+		for _ in 0..255 {
+			assert_eq!(system.inc_nonce(&"alice"), Ok(()));
+		}
+		assert_eq!(system.nonce(&"alice"), 255);
+
+		assert_eq!(system.inc_nonce(&"alice"), Err(DispatchError::NonceOverflow));
+		assert_eq!(system.nonce(&"alice"), 255);
+	}
+
+	#[test]
+	fn block_number_overflow() {
+		let mut system = super::Pallet::<SmallBlockNumberConfig>::new();
+
+		for _ in 0..255 {
+			assert_eq!(system.inc_block_number(), Ok(()));
+		}
+		assert_eq!(system.block_number(), 255);
+
+		assert_eq!(system.inc_block_number(), Err(DispatchError::BlockNumberOverflow));
+		assert_eq!(system.block_number(), 255);
+	}
+
+	#[test]
+	fn reset_nonce() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+
+		for _ in 0..3 {
+			system.inc_nonce(&alice).unwrap();
+		}
+		system.inc_nonce(&bob).unwrap();
+		assert_eq!(system.nonce(&alice), 3);
+		assert_eq!(system.all_nonces(), vec![(alice, 3), (bob, 1)]);
+
+		system.reset_nonce(&alice);
+		assert_eq!(system.nonce(&alice), 0);
+		assert_eq!(system.all_nonces(), vec![(bob, 1)]);
+
+		// Resetting an account which has never made a transaction does nothing.
+		system.reset_nonce(&"charlie");
+		assert_eq!(system.all_nonces(), vec![(bob, 1)]);
+	}
+
+	#[test]
+	fn max_events() {
+		let mut system = super::Pallet::<MaxEventsConfig>::new();
+
+		for event in ["first", "second", "third", "fourth", "fifth"] {
+			system.deposit_event(event);
+		}
+		// Only the most recent events are kept, in the order they were deposited.
+		assert_eq!(system.events(), vec![&"third", &"fourth", &"fifth"]);
+	}
+
+	#[test]
+	fn account_count() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+		let bob = "bob";
+		assert_eq!(system.account_count(), 0);
+
+		// Alice is kept alive by both their nonce and another provider, like a balance.
+		system.inc_nonce(&alice).unwrap();
+		system.inc_nonce(&alice).unwrap();
+		system.inc_providers(&alice);
+		system.inc_providers(&bob);
+		assert_eq!(system.providers(&alice), 2);
+		assert_eq!(system.account_count(), 2);
+
+		assert!(system.dec_providers(&bob));
+		assert_eq!(system.account_count(), 1);
+		assert!(!system.dec_providers(&bob));
+		assert_eq!(system.account_count(), 1);
+
+		system.reset_nonce(&alice);
+		assert_eq!(system.account_count(), 1);
+		assert!(system.dec_providers(&alice));
+		assert_eq!(system.account_count(), 0);
+	}
+
+	#[test]
+	fn estimated_elapsed_ms() {
+		let mut system = super::Pallet::<TestConfig>::new();
+		assert_eq!(system.estimated_elapsed_ms(), 0);
+
+		// Blocks take 6000ms by default.
+		system.set_block_number(10);
+		assert_eq!(system.estimated_elapsed_ms(), 60_000);
+	}
 }
@@ -0,0 +1,120 @@
+use num::Zero;
+
+use crate::support::{DispatchError, DispatchResult};
+
+pub trait Config: crate::system::Config {
+	/// The type used to represent a point in time, for example milliseconds since the unix epoch.
+	type Moment: Copy + Ord + Zero;
+}
+
+/// This is the Timestamp Module.
+/// It keeps track of the wall-clock time of the current block.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::Moment: serde::Serialize",
+		deserialize = "T::Moment: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// The current time.
+	now: T::Moment,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Timestamp Module.
+	pub fn new() -> Self {
+		Self {
+			now: T::Moment::zero(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the current time.
+	#[cfg(test)]
+	pub fn now(&self) -> T::Moment {
+		self.now
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Set the current time to `now`. This is an inherent, so it is unsigned.
+	/// This function will return an error if `now` is not strictly greater than the current time.
+	#[unsigned]
+	pub fn set(&mut self, now: T::Moment) -> DispatchResult {
+		if now <= self.now {
+			return Err(DispatchError::TimestampNotIncreasing);
+		}
+		self.now = now;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::support::DispatchError;
+
+	struct TestConfig;
+
+	impl super::Config for TestConfig {
+		type Moment = u64;
+	}
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	#[test]
+	fn set_timestamp() {
+		let mut timestamp = super::Pallet::<TestConfig>::new();
+		assert_eq!(timestamp.now(), 0);
+
+		assert!(timestamp.set(1_000).is_ok());
+		assert_eq!(timestamp.now(), 1_000);
+
+		assert!(timestamp.set(2_000).is_ok());
+		assert_eq!(timestamp.now(), 2_000);
+	}
+
+	#[test]
+	fn reject_non_increasing_timestamp() {
+		let mut timestamp = super::Pallet::<TestConfig>::new();
+		assert!(timestamp.set(1_000).is_ok());
+
+		assert_eq!(timestamp.set(1_000), Err(DispatchError::TimestampNotIncreasing));
+		assert_eq!(timestamp.set(999), Err(DispatchError::TimestampNotIncreasing));
+		assert_eq!(timestamp.now(), 1_000);
+
+		// The initial time is zero, so zero can never be set.
+		let mut timestamp = super::Pallet::<TestConfig>::new();
+		assert_eq!(timestamp.set(0), Err(DispatchError::TimestampNotIncreasing));
+	}
+}
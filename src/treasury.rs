@@ -0,0 +1,145 @@
+use num::{CheckedAdd, CheckedSub, Zero};
+
+use crate::support::{DispatchError, DispatchResult};
+
+pub trait Config: crate::balances::Config {
+	/// The account which is allowed to spend the funds of the treasury.
+	fn spend_origin() -> Self::AccountId;
+}
+
+/// This is the Treasury Module.
+/// It keeps a pot of funds, collected from the fees paid for calls, which can be spent on
+/// beneficiaries. The funds of the pot are not part of the balances of any account until they are
+/// spent.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::Balance: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, T::Balance: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// The funds of the treasury.
+	pot: T::Balance,
+	/// The spends which have just been approved, with their beneficiary. The runtime takes them
+	/// right after the spend is dispatched, and pays them into the balances of the beneficiaries.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	payouts: Vec<(T::AccountId, T::Balance)>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Treasury Module.
+	pub fn new() -> Self {
+		Self {
+			pot: T::Balance::zero(),
+			payouts: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the funds of the treasury.
+	pub fn pot(&self) -> T::Balance {
+		self.pot
+	}
+
+	/// Add `amount` to the funds of the treasury, such as the fees collected for a call.
+	/// This function will return an error if the pot overflows.
+	pub fn deposit(&mut self, amount: T::Balance) -> DispatchResult {
+		self.pot = self.pot.checked_add(&amount).ok_or(DispatchError::Overflow)?;
+		Ok(())
+	}
+
+	/// Take the approved spends, with their beneficiary, in the order they were approved.
+	pub fn take_payouts(&mut self) -> Vec<(T::AccountId, T::Balance)> {
+		core::mem::take(&mut self.payouts)
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Spend `amount` from the funds of the treasury on the beneficiary `to`.
+	/// This function will return an error if the caller is not the spend origin, or if the
+	/// treasury does not have `amount` funds.
+	pub fn spend(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if caller != T::spend_origin() {
+			return Err(DispatchError::BadOrigin);
+		}
+		self.pot = self.pot.checked_sub(&amount).ok_or(DispatchError::InsufficientBalance)?;
+		self.payouts.push((to, amount));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::support::DispatchError;
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = crate::balances::Event<Self>;
+		type Hash = u64;
+	}
+
+	impl crate::balances::Config for TestConfig {
+		type Balance = u128;
+	}
+
+	impl super::Config for TestConfig {
+		fn spend_origin() -> Self::AccountId {
+			"council"
+		}
+	}
+
+	#[test]
+	fn spend_from_pot() {
+		let mut treasury = super::Pallet::<TestConfig>::new();
+
+		assert_eq!(treasury.deposit(3), Ok(()));
+		assert_eq!(treasury.deposit(7), Ok(()));
+		assert_eq!(treasury.pot(), 10);
+
+		assert_eq!(treasury.spend("alice", "alice", 5), Err(DispatchError::BadOrigin));
+		assert_eq!(treasury.spend("council", "alice", 11), Err(DispatchError::InsufficientBalance));
+		assert_eq!(treasury.pot(), 10);
+		assert!(treasury.take_payouts().is_empty());
+
+		assert_eq!(treasury.spend("council", "alice", 4), Ok(()));
+		assert_eq!(treasury.spend("council", "bob", 2), Ok(()));
+		assert_eq!(treasury.pot(), 4);
+		assert_eq!(treasury.take_payouts(), vec![("alice", 4), ("bob", 2)]);
+		assert!(treasury.take_payouts().is_empty());
+	}
+}
@@ -0,0 +1,279 @@
+use num::{CheckedMul, CheckedSub, Zero};
+
+use crate::support::{DispatchError, DispatchResult, StorageMap};
+
+pub trait Config: crate::system::Config + crate::balances::Config<Balance: CheckedMul> {
+	/// Convert a number of blocks into a balance, so it can be multiplied by the amount which
+	/// vests per block.
+	fn block_number_to_balance(n: Self::BlockNumber) -> Self::Balance;
+}
+
+/// A vesting schedule: `locked` funds which unlock by `per_block` every block after
+/// `start_block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VestingSchedule<Balance, BlockNumber> {
+	/// The total amount of funds which vest.
+	pub locked: Balance,
+	/// The amount of funds which unlock every block.
+	pub per_block: Balance,
+	/// The block after which the funds start to unlock.
+	pub start_block: BlockNumber,
+}
+
+/// This is the Vesting Module.
+/// It keeps funds which unlock linearly over a range of blocks. The funds of a schedule are taken
+/// out of the balance of the account which adds it, and are not part of the balances of the
+/// beneficiary until they are claimed: claiming pays the unlocked funds into the free balance of
+/// the beneficiary.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::Balance: serde::Serialize, \
+			T::BlockNumber: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, T::Balance: serde::Deserialize<'de>, \
+			T::BlockNumber: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A storage map from each beneficiary to their vesting schedule.
+	schedules: StorageMap<T::AccountId, VestingSchedule<T::Balance, T::BlockNumber>>,
+	/// A storage map from each beneficiary to the vested funds they have already claimed.
+	claimed: StorageMap<T::AccountId, T::Balance>,
+	/// The current block number, which is noted at the start of each block.
+	block_number: T::BlockNumber,
+	/// The funds which have just been locked in new schedules, with the account which pays them
+	/// and the beneficiary. The runtime takes them right after the call is dispatched, and
+	/// withdraws them from the balances of the payers, like a transfer to the beneficiaries.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	deposits: Vec<(T::AccountId, T::AccountId, T::Balance)>,
+	/// The funds which have just been claimed, with their beneficiary. The runtime takes them
+	/// right after the call is dispatched, and pays them into the balances of the beneficiaries.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	payouts: Vec<(T::AccountId, T::Balance)>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Vesting Module.
+	pub fn new() -> Self {
+		Self {
+			schedules: StorageMap::new(),
+			claimed: StorageMap::new(),
+			block_number: T::BlockNumber::zero(),
+			deposits: Vec::new(),
+			payouts: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the vesting schedule (if any) of `who`.
+	pub fn schedule(
+		&self,
+		who: &T::AccountId,
+	) -> Option<&VestingSchedule<T::Balance, T::BlockNumber>> {
+		self.schedules.get(who)
+	}
+
+	/// Add a vesting schedule for the `beneficiary`.
+	/// This function will return an error if the beneficiary already has a vesting schedule, or if
+	/// nothing unlocks per block.
+	pub fn add_vesting_schedule(
+		&mut self,
+		beneficiary: T::AccountId,
+		locked: T::Balance,
+		per_block: T::Balance,
+		start_block: T::BlockNumber,
+	) -> DispatchResult {
+		if self.schedules.contains_key(&beneficiary) {
+			return Err(DispatchError::VestingScheduleExists);
+		}
+		if per_block.is_zero() {
+			return Err(DispatchError::VestingNeverUnlocks);
+		}
+		self.schedules
+			.insert(beneficiary, VestingSchedule { locked, per_block, start_block });
+		Ok(())
+	}
+
+	/// Get the funds of `who` which have unlocked at the block `now`, including the ones which
+	/// have already been claimed.
+	pub fn vested_balance(&self, who: &T::AccountId, now: T::BlockNumber) -> T::Balance {
+		let Some(schedule) = self.schedules.get(who) else { return T::Balance::zero() };
+		let blocks = match now.checked_sub(&schedule.start_block) {
+			Some(blocks) => T::block_number_to_balance(blocks),
+			None => return T::Balance::zero(),
+		};
+		// If the unlocked amount overflows, it is certainly more than the locked funds.
+		match schedule.per_block.checked_mul(&blocks) {
+			Some(vested) if vested < schedule.locked => vested,
+			_ => schedule.locked,
+		}
+	}
+
+	/// Take the funds locked in new schedules, with the account which pays them and the
+	/// beneficiary, in the order the schedules were added.
+	pub fn take_deposits(&mut self) -> Vec<(T::AccountId, T::AccountId, T::Balance)> {
+		core::mem::take(&mut self.deposits)
+	}
+
+	/// Take the claimed funds, with their beneficiary, in the order they were claimed.
+	pub fn take_payouts(&mut self) -> Vec<(T::AccountId, T::Balance)> {
+		core::mem::take(&mut self.payouts)
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {
+	fn on_initialize(&mut self, n: T::BlockNumber) {
+		self.block_number = n;
+	}
+}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Lock `locked` funds of the caller in a vesting schedule for the beneficiary `to`, which
+	/// unlock by `per_block` every block after `start_block`.
+	/// This function will return an error if the beneficiary already has a vesting schedule.
+	pub fn vested_transfer(
+		&mut self,
+		caller: T::AccountId,
+		to: T::AccountId,
+		locked: T::Balance,
+		per_block: T::Balance,
+		start_block: T::BlockNumber,
+	) -> DispatchResult {
+		self.add_vesting_schedule(to.clone(), locked, per_block, start_block)?;
+		self.deposits.push((caller, to, locked));
+		Ok(())
+	}
+
+	/// Pay the funds of the caller which have unlocked at the current block, and which have not
+	/// been claimed yet, into their free balance.
+	/// The vesting schedule is removed once all of its funds are claimed.
+	/// This function will return an error if the caller has no vesting schedule.
+	pub fn claim_vested(&mut self, caller: T::AccountId) -> DispatchResult {
+		let locked = self.schedules.get(&caller).ok_or(DispatchError::NotVesting)?.locked;
+		let vested = self.vested_balance(&caller, self.block_number);
+		let claimed = self.claimed.get_or_default(&caller);
+		// The vested funds never decrease, and the claimed funds never exceed them.
+		let claimable = vested.checked_sub(&claimed).unwrap_or(T::Balance::zero());
+
+		if vested == locked {
+			self.schedules.remove(&caller);
+			self.claimed.remove(&caller);
+		} else {
+			self.claimed.insert(caller.clone(), vested);
+		}
+		if !claimable.is_zero() {
+			self.payouts.push((caller, claimable));
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::support::{DispatchError, Hooks};
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct TestConfig;
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = crate::balances::Event<Self>;
+		type Hash = u64;
+	}
+
+	impl crate::balances::Config for TestConfig {
+		type Balance = u128;
+	}
+
+	impl super::Config for TestConfig {
+		fn block_number_to_balance(n: Self::BlockNumber) -> Self::Balance {
+			n.into()
+		}
+	}
+
+	#[test]
+	fn vested_balance() {
+		let mut vesting = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		assert_eq!(vesting.vested_balance(&alice, 10), 0);
+
+		// 100 units, unlocking 10 per block after block 5.
+		assert_eq!(vesting.add_vesting_schedule(alice, 100, 10, 5), Ok(()));
+		assert_eq!(
+			vesting.add_vesting_schedule(alice, 50, 5, 5),
+			Err(DispatchError::VestingScheduleExists)
+		);
+
+		assert_eq!(vesting.vested_balance(&alice, 0), 0);
+		assert_eq!(vesting.vested_balance(&alice, 5), 0);
+		assert_eq!(vesting.vested_balance(&alice, 6), 10);
+		assert_eq!(vesting.vested_balance(&alice, 12), 70);
+		assert_eq!(vesting.vested_balance(&alice, 15), 100);
+		assert_eq!(vesting.vested_balance(&alice, 100), 100);
+	}
+
+	#[test]
+	fn claim_vested() {
+		let mut vesting = super::Pallet::<TestConfig>::new();
+		let alice = "alice";
+
+		assert_eq!(vesting.claim_vested(alice), Err(DispatchError::NotVesting));
+
+		assert_eq!(vesting.vested_transfer("bob", alice, 100, 10, 5), Ok(()));
+		assert_eq!(
+			vesting.vested_transfer("bob", alice, 50, 5, 5),
+			Err(DispatchError::VestingScheduleExists)
+		);
+		assert_eq!(
+			vesting.vested_transfer("bob", "charlie", 50, 0, 5),
+			Err(DispatchError::VestingNeverUnlocks)
+		);
+		assert_eq!(vesting.take_deposits(), vec![("bob", alice, 100)]);
+
+		// Partially vested.
+		vesting.on_initialize(8);
+		assert_eq!(vesting.claim_vested(alice), Ok(()));
+		assert_eq!(vesting.take_payouts(), vec![(alice, 30)]);
+
+		// Claiming again at the same block does not release anything more.
+		assert_eq!(vesting.claim_vested(alice), Ok(()));
+		assert!(vesting.take_payouts().is_empty());
+
+		vesting.on_initialize(11);
+		assert_eq!(vesting.claim_vested(alice), Ok(()));
+		assert_eq!(vesting.take_payouts(), vec![(alice, 30)]);
+
+		// Fully vested, so the schedule is removed.
+		vesting.on_initialize(20);
+		assert_eq!(vesting.claim_vested(alice), Ok(()));
+		assert_eq!(vesting.take_payouts(), vec![(alice, 40)]);
+		assert!(vesting.schedule(&alice).is_none());
+		assert_eq!(vesting.claim_vested(alice), Err(DispatchError::NotVesting));
+	}
+}
@@ -0,0 +1,259 @@
+use num::{CheckedAdd, Zero};
+use std::collections::BTreeMap;
+
+use crate::support::{DispatchError, DispatchResult, StorageMap};
+
+pub trait Config: crate::balances::Config {
+	/// The type used to identify each proposal.
+	type ProposalId: Ord + Clone;
+}
+
+/// The votes cast on a proposal: whether each voter approves it, and the weight of their vote.
+pub type Votes<AccountId, Balance> = BTreeMap<AccountId, (bool, Balance)>;
+
+/// This is the Voting Module.
+/// It allows accounts to create proposals, and to vote on them. Votes are weighted by the balance
+/// each voter had when they voted, and a proposal is approved by a simple majority of that weight.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+	feature = "serde",
+	serde(bound(
+		serialize = "T::AccountId: serde::Serialize, T::ProposalId: serde::Serialize, \
+			T::Balance: serde::Serialize",
+		deserialize = "T::AccountId: serde::Deserialize<'de>, \
+			T::ProposalId: serde::Deserialize<'de>, T::Balance: serde::Deserialize<'de>",
+	))
+)]
+pub struct Pallet<T: Config> {
+	/// A storage map from each proposal to the account which created it.
+	proposals: StorageMap<T::ProposalId, T::AccountId>,
+	/// A storage map from each proposal to the votes cast on it, where `true` is an approval, with
+	/// the weight of each vote. Each account can only vote once per proposal.
+	votes: StorageMap<T::ProposalId, Votes<T::AccountId, T::Balance>>,
+	/// The votes which have just been cast, which the runtime weighs right after the call is
+	/// dispatched, by the balance of the voter at that time.
+	#[cfg_attr(feature = "serde", serde(skip))]
+	unweighed: Vec<(T::ProposalId, T::AccountId)>,
+	/// The version of the storage layout of this module, which is upgraded by its migrations.
+	#[cfg_attr(feature = "serde", serde(default))]
+	storage_version: u16,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Create a new instance of the Voting Module.
+	pub fn new() -> Self {
+		Self {
+			proposals: StorageMap::new(),
+			votes: StorageMap::new(),
+			unweighed: Vec::new(),
+			storage_version: <Self as crate::support::Migrate>::STORAGE_VERSION,
+		}
+	}
+
+	/// Get the vote (if any) of `who` on a proposal.
+	#[cfg(test)]
+	pub fn vote_of(&self, proposal_id: &T::ProposalId, who: &T::AccountId) -> Option<bool> {
+		self.votes
+			.get(proposal_id)
+			.and_then(|votes| votes.get(who))
+			.map(|(approve, _)| *approve)
+	}
+
+	/// Weigh the votes which have just been cast, by the balance of each voter as given by
+	/// `balance_of`.
+	pub fn weigh_votes(&mut self, balance_of: impl Fn(&T::AccountId) -> T::Balance) {
+		for (proposal_id, voter) in core::mem::take(&mut self.unweighed) {
+			if let Some((_, weight)) =
+				self.votes.get_mut(&proposal_id).and_then(|votes| votes.get_mut(&voter))
+			{
+				*weight = balance_of(&voter);
+			}
+		}
+	}
+
+	/// Tally the votes of a proposal, weighting each vote by the balance the voter had when they
+	/// voted.
+	/// Returns `None` if the proposal does not exist, otherwise whether a strict majority of the
+	/// weight approves the proposal.
+	pub fn result(&self, proposal_id: &T::ProposalId) -> Option<bool> {
+		let votes = self.votes.get(proposal_id)?;
+		let mut ayes = T::Balance::zero();
+		let mut nays = T::Balance::zero();
+		for (approve, weight) in votes.values() {
+			let side = if *approve { &mut ayes } else { &mut nays };
+			// The weight of a side can only overflow if it exceeds the total issuance, so we cap
+			// it instead.
+			*side = side.checked_add(weight).unwrap_or(*side);
+		}
+		Some(ayes > nays)
+	}
+}
+
+impl<T: Config> crate::support::Hooks<T::BlockNumber> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnKilledAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::OnNewAccount<T::AccountId> for Pallet<T> {}
+
+impl<T: Config> crate::support::Migrate for Pallet<T> {
+	fn storage_version(&self) -> u16 {
+		self.storage_version
+	}
+
+	fn set_storage_version(&mut self, version: u16) {
+		self.storage_version = version;
+	}
+}
+
+impl<T: Config, E> crate::support::TakeEvents<E> for Pallet<T> {}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+	/// Create a new proposal on behalf of the `caller`.
+	/// This function will return an error if the proposal already exists.
+	pub fn create_proposal(
+		&mut self,
+		caller: T::AccountId,
+		proposal_id: T::ProposalId,
+	) -> DispatchResult {
+		if self.proposals.contains_key(&proposal_id) {
+			return Err(DispatchError::ProposalAlreadyExists);
+		}
+		self.votes.insert(proposal_id.clone(), BTreeMap::new());
+		self.proposals.insert(proposal_id, caller);
+		Ok(())
+	}
+
+	/// Vote on an existing proposal on behalf of the `caller`, approving it or not. The vote is
+	/// weighed by the balance of the caller once the call is dispatched.
+	/// This function will return an error if the proposal does not exist, or if the caller has
+	/// already voted on it.
+	pub fn vote(
+		&mut self,
+		caller: T::AccountId,
+		proposal_id: T::ProposalId,
+		approve: bool,
+	) -> DispatchResult {
+		let votes = self.votes.get_mut(&proposal_id).ok_or(DispatchError::ProposalNotFound)?;
+		if votes.contains_key(&caller) {
+			return Err(DispatchError::AlreadyVoted);
+		}
+		votes.insert(caller.clone(), (approve, T::Balance::zero()));
+		self.unweighed.push((proposal_id, caller));
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::support::DispatchError;
+
+	struct TestConfig;
+
+	impl super::Config for TestConfig {
+		type ProposalId = u32;
+	}
+
+	impl crate::balances::Config for TestConfig {
+		type Balance = u128;
+	}
+
+	impl crate::system::Config for TestConfig {
+		type AccountId = &'static str;
+		type BlockNumber = u32;
+		type Nonce = u32;
+		type RuntimeEvent = ();
+		type Hash = u64;
+	}
+
+	#[test]
+	fn create_proposal() {
+		let mut voting = super::Pallet::<TestConfig>::new();
+		assert_eq!(voting.result(&1), None);
+
+		assert!(voting.create_proposal("alice", 1).is_ok());
+		assert_eq!(voting.create_proposal("bob", 1), Err(DispatchError::ProposalAlreadyExists));
+
+		// A proposal without votes is not approved.
+		assert_eq!(voting.result(&1), Some(false));
+	}
+
+	#[test]
+	fn simple_majority() {
+		let mut voting = super::Pallet::<TestConfig>::new();
+		// With equal balances, every vote has the same weight.
+		let mut balances = crate::balances::Pallet::<TestConfig>::new();
+		balances.set_balance(&"alice", 1);
+		balances.set_balance(&"bob", 1);
+		balances.set_balance(&"charlie", 1);
+
+		assert_eq!(voting.vote("alice", 1, true), Err(DispatchError::ProposalNotFound));
+
+		assert!(voting.create_proposal("alice", 1).is_ok());
+		assert!(voting.vote("alice", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert!(voting.vote("bob", 1, false).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		// A tie is not a majority.
+		assert_eq!(voting.result(&1), Some(false));
+
+		assert!(voting.vote("charlie", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert_eq!(voting.result(&1), Some(true));
+		assert_eq!(voting.vote_of(&1, &"bob"), Some(false));
+	}
+
+	#[test]
+	fn double_vote() {
+		let mut voting = super::Pallet::<TestConfig>::new();
+
+		assert!(voting.create_proposal("alice", 1).is_ok());
+		assert!(voting.vote("bob", 1, true).is_ok());
+		assert_eq!(voting.vote("bob", 1, false), Err(DispatchError::AlreadyVoted));
+		assert_eq!(voting.vote_of(&1, &"bob"), Some(true));
+	}
+
+	#[test]
+	fn weighted_majority() {
+		let mut voting = super::Pallet::<TestConfig>::new();
+		let mut balances = crate::balances::Pallet::<TestConfig>::new();
+		balances.set_balance(&"alice", 100);
+		balances.set_balance(&"bob", 30);
+		balances.set_balance(&"charlie", 30);
+		balances.set_balance(&"dave", 30);
+
+		assert!(voting.create_proposal("alice", 1).is_ok());
+		assert!(voting.vote("bob", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert!(voting.vote("charlie", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert!(voting.vote("dave", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert_eq!(voting.result(&1), Some(true));
+
+		// A single voter with a larger balance outvotes the majority of accounts.
+		assert!(voting.vote("alice", 1, false).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert_eq!(voting.result(&1), Some(false));
+	}
+
+	#[test]
+	fn moved_funds_keep_their_weight() {
+		let mut voting = super::Pallet::<TestConfig>::new();
+		let mut balances = crate::balances::Pallet::<TestConfig>::new();
+		balances.set_balance(&"alice", 100);
+		balances.set_balance(&"bob", 60);
+
+		assert!(voting.create_proposal("alice", 1).is_ok());
+		assert!(voting.vote("alice", 1, true).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert!(voting.vote("bob", 1, false).is_ok());
+		voting.weigh_votes(|who| balances.balance(who));
+		assert_eq!(voting.result(&1), Some(true));
+
+		// Moving funds after voting does not change the weight of the votes already cast.
+		assert!(balances.force_transfer("alice", "charlie", 100).is_ok());
+		assert_eq!(voting.result(&1), Some(true));
+	}
+}